@@ -0,0 +1,113 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use turn_base_demo::{
+    Attribute, AttributeConstraint, AttributeConstraintProcessorContainer, AttributeId,
+    AttributeModifier, AttributeSet, ModifierOperation,
+};
+
+fn new_attribute(name: &str, value: f32) -> Attribute {
+    let mut attribute = Attribute::default();
+    attribute.attribute_name = name.into();
+    attribute.set_base_value(value);
+    attribute
+}
+
+fn new_modifier(operation: ModifierOperation, value: f32) -> AttributeModifier {
+    let mut modifier = AttributeModifier::empty();
+    modifier.operation = operation;
+    modifier.value = value;
+    modifier
+}
+
+fn recalculate_current_value(c: &mut Criterion) {
+    let mut attribute = new_attribute("attack", 100.0);
+
+    for i in 0..10 {
+        attribute.add_modifier(&new_modifier(ModifierOperation::Absolute, i as f32));
+        attribute.add_modifier(&new_modifier(ModifierOperation::Percentage, 0.01));
+    }
+
+    attribute.add_modifier(&new_modifier(ModifierOperation::Override, 50.0));
+
+    c.bench_function("recalculate_current_value", |b| {
+        b.iter(|| {
+            attribute.recalculate_current_value();
+            black_box(attribute.get_current_value())
+        })
+    });
+}
+
+fn new_constraint_attribute_set(count: usize) -> (AttributeSet, Vec<AttributeId>) {
+    let mut set = AttributeSet::default();
+    let mut ids = vec![];
+
+    for i in 0..count {
+        let name = format!("resource_{i}");
+        let max_name = format!("max_resource_{i}");
+
+        set.insert_attribute(new_attribute(&name, 100.0));
+        set.insert_attribute(new_attribute(&max_name, 100.0));
+        set.insert_constraint(AttributeConstraint::new(
+            max_name.as_str(),
+            name.as_str(),
+            "ratio",
+        ));
+
+        ids.push(AttributeId::new(&max_name));
+    }
+
+    (set, ids)
+}
+
+fn constraint_propagation(c: &mut Criterion) {
+    let container = AttributeConstraintProcessorContainer::new();
+    let (mut set, ids) = new_constraint_attribute_set(16);
+
+    c.bench_function("constraint_propagation", |b| {
+        let mut value = 100.0;
+
+        b.iter(|| {
+            value = if value == 100.0 { 200.0 } else { 100.0 };
+
+            for id in ids.iter() {
+                set.update_attribute_base_value(*id, value, &container);
+            }
+        })
+    });
+
+    c.bench_function("constraint_propagation_transaction", |b| {
+        let mut value = 100.0;
+
+        b.iter(|| {
+            value = if value == 100.0 { 200.0 } else { 100.0 };
+
+            let mut transaction = set.begin_transaction();
+
+            for id in ids.iter() {
+                transaction.set_base_value(*id, value);
+            }
+
+            transaction.commit(&container).unwrap();
+        })
+    });
+}
+
+fn attribute_lookup(c: &mut Criterion) {
+    let (set, _) = new_constraint_attribute_set(16);
+    let id = AttributeId::new("resource_8");
+
+    c.bench_function("attribute_lookup_by_id", |b| {
+        b.iter(|| black_box(set.get_current_value(black_box(id))))
+    });
+
+    c.bench_function("attribute_lookup_by_name", |b| {
+        b.iter(|| black_box(set.get_current_value(black_box("resource_8"))))
+    });
+}
+
+criterion_group!(
+    benches,
+    recalculate_current_value,
+    constraint_propagation,
+    attribute_lookup
+);
+criterion_main!(benches);
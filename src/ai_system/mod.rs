@@ -0,0 +1,131 @@
+mod pattern;
+mod random;
+mod state;
+mod utility;
+
+pub use pattern::*;
+pub use random::*;
+pub use state::*;
+pub use utility::*;
+
+use crate::{
+    AttributeUtils, IdLookup, NumberGeneratorContainer, PendingSkill, Skill, SkillComponent,
+    SkillEffectProcessorContainer, SkillTag,
+};
+
+///AI 可以选择的目标
+pub struct AiTarget<'a> {
+    pub skill_component: &'a SkillComponent,
+    ///是否为施法者的友方
+    pub ally: bool,
+    ///目标正在蓄力或者引导的技能
+    pub pending: Option<&'a PendingSkill>,
+    ///施法者对目标的仇恨
+    pub threat: f32,
+    ///目标是否已经消失，例如到期的召唤物
+    pub removed: bool,
+}
+
+impl AiTarget<'_> {
+    pub fn health(&self) -> f32 {
+        self.skill_component
+            .attribute_set
+            .get_current_value(AttributeUtils::HEALTH)
+            .unwrap_or(0.0)
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.health() > 0.0
+    }
+}
+
+///AI 决策时的上下文
+pub struct AiContext<'a> {
+    ///施法者，可以使用的技能为技能书中装备的技能
+    pub caster: &'a SkillComponent,
+    pub targets: &'a [AiTarget<'a>],
+    pub processors: &'a SkillEffectProcessorContainer,
+    ///嘲讽施法者的目标的下标，施法者选择的敌方目标会被替换为这个目标
+    pub taunt: Option<usize>,
+}
+
+impl<'a> AiContext<'a> {
+    ///施法者装备的技能，技能的下标为在其中的位置
+    pub fn skills(&self) -> impl Iterator<Item = &'a Skill> + use<'a> {
+        self.caster.skills()
+    }
+
+    pub fn skill(&self, skill: usize) -> Option<&'a Skill> {
+        self.skills().nth(skill)
+    }
+
+    ///存活目标的下标
+    pub fn alive_targets(&self) -> impl Iterator<Item = usize> {
+        self.targets
+            .iter()
+            .enumerate()
+            .filter(|(_, target)| target.is_alive())
+            .map(|(index, _)| index)
+    }
+
+    ///满足技能目标规则的目标的下标
+    pub fn valid_targets(&self, skill: &Skill) -> impl Iterator<Item = usize> {
+        self.targets
+            .iter()
+            .enumerate()
+            .filter(|(_, target)| {
+                !target.removed && skill.target.allows(self.caster, target.skill_component)
+            })
+            .map(|(index, _)| index)
+    }
+
+    ///存活的敌方目标中仇恨最高的下标，没有仇恨时返回 None
+    pub fn highest_threat(&self) -> Option<usize> {
+        self.alive_targets()
+            .filter(|index| !self.targets[*index].ally && self.targets[*index].threat > 0.0)
+            .max_by(|a, b| self.targets[*a].threat.total_cmp(&self.targets[*b].threat))
+    }
+
+    pub fn find_skill(&self, skill_name: &str) -> Option<usize> {
+        self.skills()
+            .position(|skill| skill.skill_name == skill_name)
+    }
+
+    ///施法者现在是否可以使用第 skill 个技能，考虑冷却、消耗和状态
+    pub fn can_use(&self, skill: usize) -> bool {
+        self.skill(skill)
+            .is_some_and(|skill| self.caster.can_use(skill).is_ok())
+    }
+
+    ///带有 tag 标签的技能的下标
+    pub fn skills_with_tag(&self, tag: impl IdLookup<SkillTag>) -> impl Iterator<Item = usize> {
+        let tag = tag.lookup();
+
+        self.skills()
+            .enumerate()
+            .filter(move |(_, skill)| tag.is_some_and(|tag| skill.tags.contains(&tag)))
+            .map(|(index, _)| index)
+    }
+}
+
+///AI 选择的行动，skill 和 target 为 AiContext 中的下标
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AiAction {
+    pub skill: usize,
+    pub target: usize,
+}
+
+///为非玩家角色选择技能和目标
+pub trait BattleAi: 'static {
+    ///没有可以执行的行动时返回 None
+    fn choose_action(
+        &mut self,
+        context: &AiContext,
+        generators: &mut NumberGeneratorContainer,
+    ) -> Option<AiAction>;
+
+    ///保存 AI 的状态，无法保存时返回 None
+    fn state(&self) -> Option<AiState> {
+        None
+    }
+}
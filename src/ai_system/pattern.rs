@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{NumberGeneratorContainer, Skill};
+
+use super::{AiAction, AiContext, AiState, BattleAi};
+
+///目标选择方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TargetSelector {
+    #[default]
+    First,
+    LowestHealth,
+    HighestHealth,
+    ///仇恨最高的敌方目标，没有仇恨时选择第一个
+    HighestThreat,
+}
+
+///固定行动模式中的一步
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatternStep {
+    pub skill_name: String,
+    #[serde(default)]
+    pub selector: TargetSelector,
+    ///为 true 时只选择友方目标，否则只选择敌方目标
+    #[serde(default)]
+    pub ally: bool,
+}
+
+///按照固定的模式循环使用技能，例如首领的 "攻击、攻击、治疗"
+///
+///当前一步没有可用的技能或者目标时跳到下一步
+#[derive(Debug, Clone, Default)]
+pub struct PatternBattleAi {
+    pub steps: Vec<PatternStep>,
+    index: usize,
+}
+
+impl PatternBattleAi {
+    pub fn new(steps: Vec<PatternStep>) -> Self {
+        Self { steps, index: 0 }
+    }
+
+    ///从第 index 步开始，例如恢复保存的 AI
+    pub fn with_index(mut self, index: usize) -> Self {
+        self.index = if self.steps.is_empty() {
+            0
+        } else {
+            index % self.steps.len()
+        };
+        self
+    }
+
+    ///下一次行动使用的步骤
+    pub fn current_step(&self) -> Option<&PatternStep> {
+        self.steps.get(self.index)
+    }
+
+    fn select_target(context: &AiContext, skill: &Skill, step: &PatternStep) -> Option<usize> {
+        let candidates = context
+            .valid_targets(skill)
+            .filter(|index| context.targets[*index].ally == step.ally);
+
+        let health = |index: &usize| context.targets[*index].health();
+
+        match step.selector {
+            TargetSelector::First => candidates.min(),
+            TargetSelector::LowestHealth => {
+                candidates.min_by(|a, b| health(a).total_cmp(&health(b)))
+            }
+            TargetSelector::HighestHealth => {
+                candidates.max_by(|a, b| health(a).total_cmp(&health(b)))
+            }
+            TargetSelector::HighestThreat if !step.ally => {
+                let threat = context
+                    .highest_threat()
+                    .filter(|index| context.valid_targets(skill).any(|valid| valid == *index));
+
+                threat.or_else(|| candidates.min())
+            }
+            TargetSelector::HighestThreat => candidates.min(),
+        }
+    }
+}
+
+impl BattleAi for PatternBattleAi {
+    fn choose_action(
+        &mut self,
+        context: &AiContext,
+        _generators: &mut NumberGeneratorContainer,
+    ) -> Option<AiAction> {
+        for _ in 0..self.steps.len() {
+            let step = &self.steps[self.index];
+            self.index = (self.index + 1) % self.steps.len();
+
+            if let Some(skill) = context.find_skill(&step.skill_name)
+                && context.can_use(skill)
+                && let Some(target) = context
+                    .skill(skill)
+                    .and_then(|skill| Self::select_target(context, skill, step))
+            {
+                return Some(AiAction { skill, target });
+            }
+        }
+
+        None
+    }
+
+    fn state(&self) -> Option<AiState> {
+        Some(AiState::Pattern {
+            steps: self.steps.clone(),
+            index: self.index,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        AiAction, AiContext, AiTarget, AttributeUtils, BattleAi, NumberGeneratorContainer,
+        PatternBattleAi, PatternStep, Skill, SkillComponent, SkillEffectProcessorContainer,
+        SkillUsage, TargetSelector,
+    };
+
+    fn new_component(faction: usize, health: f32) -> SkillComponent {
+        let mut component = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        component.faction = faction;
+        component
+            .attribute_set
+            .get_mut(AttributeUtils::HEALTH)
+            .unwrap()
+            .set_current_value(health);
+
+        component
+    }
+
+    fn step(skill_name: &str, selector: TargetSelector, ally: bool) -> PatternStep {
+        PatternStep {
+            skill_name: skill_name.to_string(),
+            selector,
+            ally,
+        }
+    }
+
+    #[test]
+    fn test_pattern_battle_ai() {
+        let processors = SkillEffectProcessorContainer::new();
+        let mut caster = new_component(0, 100.0);
+        for skill in [Skill::new("slash", vec![]), Skill::new("mend", vec![])] {
+            caster
+                .skill_book
+                .learn_and_equip(skill, SkillUsage::default())
+                .unwrap();
+        }
+        let components = [
+            new_component(1, 80.0),
+            new_component(1, 30.0),
+            new_component(1, 0.0),
+            new_component(0, 100.0),
+        ];
+        let targets = components
+            .iter()
+            .map(|component| AiTarget {
+                skill_component: component,
+                ally: component.faction == caster.faction,
+                pending: None,
+                threat: 0.0,
+                removed: false,
+            })
+            .collect::<Vec<_>>();
+        let context = AiContext {
+            caster: &caster,
+            targets: &targets,
+            processors: &processors,
+            taunt: None,
+        };
+
+        let mut ai = PatternBattleAi::new(vec![
+            step("slash", TargetSelector::LowestHealth, false),
+            step("fireball", TargetSelector::First, false),
+            step("slash", TargetSelector::HighestHealth, false),
+            step("mend", TargetSelector::First, true),
+        ]);
+        let mut generators = NumberGeneratorContainer::seeded(1);
+        let mut choose = || ai.choose_action(&context, &mut generators);
+
+        //没有学会的技能被跳过，死亡的目标不会被选择
+        assert_eq!(
+            Some(AiAction {
+                skill: 0,
+                target: 1
+            }),
+            choose()
+        );
+        assert_eq!(
+            Some(AiAction {
+                skill: 0,
+                target: 0
+            }),
+            choose()
+        );
+        assert_eq!(
+            Some(AiAction {
+                skill: 1,
+                target: 3
+            }),
+            choose()
+        );
+        assert_eq!(
+            Some(AiAction {
+                skill: 0,
+                target: 1
+            }),
+            choose()
+        );
+        assert_eq!(
+            Some(&step("fireball", TargetSelector::First, false)),
+            ai.current_step()
+        );
+    }
+}
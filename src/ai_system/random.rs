@@ -0,0 +1,137 @@
+use crate::{DEFAULT_NUMBER_GENERATOR, NumberGeneratorContainer, NumberRange, Skill};
+
+use super::{AiAction, AiContext, AiState, BattleAi};
+
+///随机选择技能和满足技能目标规则的目标
+pub struct RandomBattleAi {
+    pub generator: String,
+}
+
+impl Default for RandomBattleAi {
+    fn default() -> Self {
+        Self {
+            generator: DEFAULT_NUMBER_GENERATOR.to_string(),
+        }
+    }
+}
+
+impl BattleAi for RandomBattleAi {
+    fn choose_action(
+        &mut self,
+        context: &AiContext,
+        generators: &mut NumberGeneratorContainer,
+    ) -> Option<AiAction> {
+        let targets = |skill: &Skill| context.valid_targets(skill).collect::<Vec<_>>();
+
+        let skills = context
+            .skills()
+            .enumerate()
+            .filter(|(index, skill)| context.can_use(*index) && !targets(skill).is_empty())
+            .collect::<Vec<_>>();
+
+        if skills.is_empty() {
+            return None;
+        }
+
+        let skill = generators
+            .generate(&NumberRange::new(0, skills.len()).with_name(&self.generator))
+            .ok()?;
+        let (skill, targets) = (skills[skill].0, targets(skills[skill].1));
+        let target = generators
+            .generate(&NumberRange::new(0, targets.len()).with_name(&self.generator))
+            .ok()?;
+
+        Some(AiAction {
+            skill,
+            target: targets[target],
+        })
+    }
+
+    fn state(&self) -> Option<AiState> {
+        Some(AiState::Random {
+            generator: self.generator.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        AiContext, AiTarget, AttributeUtils, BattleAi, NumberGeneratorContainer, RandomBattleAi,
+        Skill, SkillComponent, SkillEffectProcessorContainer, SkillTarget, SkillUsage,
+    };
+
+    fn new_component(faction: usize, health: f32) -> SkillComponent {
+        let mut component = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        component.faction = faction;
+        component
+            .attribute_set
+            .get_mut(AttributeUtils::HEALTH)
+            .unwrap()
+            .set_current_value(health);
+
+        component
+    }
+
+    #[test]
+    fn test_random_battle_ai() {
+        let processors = SkillEffectProcessorContainer::new();
+        let mut caster = new_component(0, 100.0);
+        for skill in [
+            Skill::new("slash", vec![]).with_target(SkillTarget::Enemy),
+            Skill::new("mend", vec![]).with_target(SkillTarget::Ally),
+        ] {
+            caster
+                .skill_book
+                .learn_and_equip(skill, SkillUsage::default())
+                .unwrap();
+        }
+        let components = [
+            new_component(1, 100.0),
+            new_component(1, 0.0),
+            new_component(0, 50.0),
+        ];
+        let targets = components
+            .iter()
+            .map(|component| AiTarget {
+                skill_component: component,
+                ally: component.faction == caster.faction,
+                pending: None,
+                threat: 0.0,
+                removed: false,
+            })
+            .collect::<Vec<_>>();
+        let context = AiContext {
+            caster: &caster,
+            targets: &targets,
+            processors: &processors,
+            taunt: None,
+        };
+
+        let mut ai = RandomBattleAi::default();
+        let mut generators = NumberGeneratorContainer::seeded(5);
+        let mut used = [false; 2];
+
+        //只选择满足技能目标规则的目标，不会选择死亡的单位
+        for _ in 0..50 {
+            let action = ai.choose_action(&context, &mut generators).unwrap();
+            used[action.skill] = true;
+
+            match action.skill {
+                0 => assert_eq!(0, action.target),
+                _ => assert_eq!(2, action.target),
+            }
+        }
+        assert_eq!([true, true], used);
+
+        //相同的种子得到相同的选择
+        let mut choose = |seed| {
+            let mut generators = NumberGeneratorContainer::seeded(seed);
+
+            (0..10)
+                .map(|_| ai.choose_action(&context, &mut generators))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(choose(9), choose(9));
+    }
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+use super::{AiWeights, BattleAi, PatternBattleAi, PatternStep, RandomBattleAi, UtilityBattleAi};
+
+///AI 保存的状态，可以恢复为完全相同的 AI
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AiState {
+    Random {
+        generator: String,
+    },
+    Utility {
+        weights: AiWeights,
+    },
+    Pattern {
+        steps: Vec<PatternStep>,
+        ///下一次行动使用的步骤
+        #[serde(default)]
+        index: usize,
+    },
+}
+
+impl AiState {
+    ///根据状态创建 AI
+    pub fn to_battle_ai(&self) -> Box<dyn BattleAi> {
+        match self {
+            AiState::Random { generator } => Box::new(RandomBattleAi {
+                generator: generator.clone(),
+            }),
+            AiState::Utility { weights } => Box::new(UtilityBattleAi::new(weights.clone())),
+            AiState::Pattern { steps, index } => {
+                Box::new(PatternBattleAi::new(steps.clone()).with_index(*index))
+            }
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{NumberGeneratorContainer, Skill};
+
+use super::{AiAction, AiContext, AiState, AiTarget, BattleAi};
+
+///效用 AI 的权重
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiWeights {
+    ///每点期望伤害的分数
+    pub damage: f32,
+    ///击杀概率为 1 时的分数
+    pub kill: f32,
+    ///每点期望治疗的分数
+    pub heal: f32,
+    ///结果过多无法枚举时，每个技能和目标采样的次数
+    pub samples: usize,
+}
+
+impl Default for AiWeights {
+    fn default() -> Self {
+        Self {
+            damage: 1.0,
+            kill: 50.0,
+            heal: 1.0,
+            samples: 8,
+        }
+    }
+}
+
+///按照原型名称保存的 AI 权重，例如 "berserker"、"healer"
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AiArchetypes(BTreeMap<String, AiWeights>);
+
+impl AiArchetypes {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn insert(&mut self, name: &str, weights: AiWeights) {
+        self.0.insert(name.to_string(), weights);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AiWeights> {
+        self.0.get(name)
+    }
+}
+
+///技能对一个目标的评估结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SkillEvaluation {
+    pub expected_damage: f32,
+    pub kill_chance: f32,
+    pub expected_heal: f32,
+    pub score: f32,
+}
+
+///效用 AI
+///
+///对每个技能和每个目标进行预览，按照权重计算分数，选择分数最高的行动
+///需要采样时使用 AI 决策的随机数生成器，不会影响战斗中的随机数
+#[derive(Debug, Clone, Default)]
+pub struct UtilityBattleAi {
+    pub weights: AiWeights,
+}
+
+impl UtilityBattleAi {
+    pub fn new(weights: AiWeights) -> Self {
+        Self { weights }
+    }
+
+    pub fn from_archetype(archetypes: &AiArchetypes, name: &str) -> Option<Self> {
+        archetypes.get(name).cloned().map(Self::new)
+    }
+
+    ///预览技能对目标的效果，技能执行失败时返回 None
+    pub fn evaluate(
+        &self,
+        context: &AiContext,
+        skill: &Skill,
+        target: &AiTarget,
+        generators: &mut NumberGeneratorContainer,
+    ) -> Option<SkillEvaluation> {
+        let preview = context
+            .processors
+            .preview_with(
+                context.caster,
+                target.skill_component,
+                skill,
+                generators,
+                self.weights.samples,
+            )
+            .ok()?;
+
+        let mut evaluation = SkillEvaluation {
+            expected_damage: preview.expected_damage,
+            kill_chance: preview.lethal_chance,
+            expected_heal: preview.expected_heal,
+            score: 0.0,
+        };
+
+        let offensive = evaluation.expected_damage * self.weights.damage
+            + evaluation.kill_chance * self.weights.kill;
+        let heal = evaluation.expected_heal * self.weights.heal;
+
+        evaluation.score = if target.ally {
+            heal - offensive
+        } else {
+            offensive - heal
+        };
+
+        Some(evaluation)
+    }
+}
+
+impl BattleAi for UtilityBattleAi {
+    fn choose_action(
+        &mut self,
+        context: &AiContext,
+        generators: &mut NumberGeneratorContainer,
+    ) -> Option<AiAction> {
+        let mut best: Option<(AiAction, f32)> = None;
+
+        for (skill_index, skill) in context.skills().enumerate() {
+            if !context.can_use(skill_index) {
+                continue;
+            }
+
+            for target_index in context.valid_targets(skill) {
+                let Some(evaluation) =
+                    self.evaluate(context, skill, &context.targets[target_index], generators)
+                else {
+                    continue;
+                };
+
+                if best.is_none_or(|(_, score)| evaluation.score > score) {
+                    best = Some((
+                        AiAction {
+                            skill: skill_index,
+                            target: target_index,
+                        },
+                        evaluation.score,
+                    ));
+                }
+            }
+        }
+
+        best.filter(|(_, score)| *score > 0.0)
+            .map(|(action, _)| action)
+    }
+
+    fn state(&self) -> Option<AiState> {
+        Some(AiState::Utility {
+            weights: self.weights.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        AiAction, AiArchetypes, AiContext, AiTarget, AttributeUtils, BattleAi,
+        DamageSkillEffectProcessor, HealSkillEffectProcessor, NumberGeneratorContainer, Skill,
+        SkillComponent, SkillEffect, SkillEffectProcessorContainer, SkillUsage, UtilityBattleAi,
+    };
+
+    fn new_skills() -> Vec<Skill> {
+        vec![
+            Skill::new(
+                "slash",
+                vec![
+                    SkillEffect::new("damage_skill_effect")
+                        .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, 30.0),
+                ],
+            ),
+            Skill::new(
+                "heal",
+                vec![
+                    SkillEffect::new("heal_skill_effect")
+                        .with_value(HealSkillEffectProcessor::MIN_HEAL, 40.0),
+                ],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_utility_battle_ai() {
+        let mut processors = SkillEffectProcessorContainer::new();
+        processors.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+        processors.register_skill_effect_processor(HealSkillEffectProcessor);
+
+        let archetypes = AiArchetypes::from_json(
+            r#"{ "healer": { "damage": 1.0, "kill": 50.0, "heal": 1.0, "samples": 1 } }"#,
+        )
+        .unwrap();
+        let mut ai = UtilityBattleAi::from_archetype(&archetypes, "healer").unwrap();
+
+        let mut caster = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        for skill in new_skills() {
+            caster
+                .skill_book
+                .learn_and_equip(skill, SkillUsage::default())
+                .unwrap();
+        }
+        let enemy = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        let mut ally = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        ally.attribute_set
+            .get_mut(AttributeUtils::HEALTH)
+            .unwrap()
+            .set_current_value(20.0);
+
+        let mut generators = NumberGeneratorContainer::seeded(1);
+
+        let action = {
+            let targets = [
+                AiTarget {
+                    skill_component: &enemy,
+                    ally: false,
+                    pending: None,
+                    threat: 0.0,
+                    removed: false,
+                },
+                AiTarget {
+                    skill_component: &ally,
+                    ally: true,
+                    pending: None,
+                    threat: 0.0,
+                    removed: false,
+                },
+            ];
+            let context = AiContext {
+                caster: &caster,
+                targets: &targets,
+                processors: &processors,
+                taunt: None,
+            };
+
+            ai.choose_action(&context, &mut generators)
+        };
+        assert_eq!(
+            Some(AiAction {
+                skill: 1,
+                target: 1
+            }),
+            action
+        );
+
+        ally.attribute_set
+            .get_mut(AttributeUtils::HEALTH)
+            .unwrap()
+            .set_current_value(100.0);
+
+        let targets = [
+            AiTarget {
+                skill_component: &enemy,
+                ally: false,
+                pending: None,
+                threat: 0.0,
+                removed: false,
+            },
+            AiTarget {
+                skill_component: &ally,
+                ally: true,
+                pending: None,
+                threat: 0.0,
+                removed: false,
+            },
+        ];
+        let context = AiContext {
+            caster: &caster,
+            targets: &targets,
+            processors: &processors,
+            taunt: None,
+        };
+        assert_eq!(
+            Some(AiAction {
+                skill: 0,
+                target: 0
+            }),
+            ai.choose_action(&context, &mut generators)
+        );
+    }
+}
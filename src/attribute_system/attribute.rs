@@ -1,17 +1,26 @@
 use bevy::platform::collections::HashMap;
+use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
 use uuid::Uuid;
 
+use crate::define_id;
+
+define_id!(
+    ///属性标识符
+    AttributeId
+);
+
 ///属性
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Attribute {
     base_value: f32,
     current_value: f32,
     pub min_value: f32,
     pub max_value: f32,
-    pub attribute_name: String,
+    pub attribute_name: AttributeId,
     pub display_name: String,
     modifiers: Vec<AttributeModifier>,
+    #[serde(with = "crate::serialize::sorted_map")]
     unique_modifiers: HashMap<String, AttributeModifier>,
 }
 
@@ -32,7 +41,7 @@ impl Default for Attribute {
 
 impl Attribute {
     pub fn set_base_value(&mut self, v: f32) {
-        self.base_value = v;
+        self.set_base_value_without_recalculate(v);
         self.recalculate_current_value();
     }
 
@@ -49,87 +58,102 @@ impl Attribute {
     }
 
     pub fn add_modifier(&mut self, modifier: &AttributeModifier) {
-        if !self.modifiers.contains(modifier) {
-            self.modifiers.push(modifier.clone());
+        if self.add_modifier_without_recalculate(modifier) {
             self.recalculate_current_value();
         }
     }
 
     pub fn insert_modifier(&mut self, modifier: &AttributeModifier) {
-        self.unique_modifiers
-            .insert(modifier.source_id.clone(), modifier.clone());
+        self.insert_modifier_without_recalculate(modifier);
         self.recalculate_current_value();
     }
 
     pub fn remove_modifier(&mut self, modifier: &AttributeModifier) {
-        if let Some(index) = self.modifiers.iter().position(|v| v.uuid == modifier.uuid) {
-            self.modifiers.remove(index);
+        if self.remove_modifier_without_recalculate(modifier) {
             self.recalculate_current_value();
         }
     }
 
-    pub fn recalculate_current_value(&mut self) {
-        self.current_value = self.base_value;
-        let mut absolute_modifiers = vec![];
-        let mut percentage_modifiers = vec![];
-        let mut override_modifiers = vec![];
-
-        for modifier in self.modifiers.iter().chain(self.unique_modifiers.values()) {
-            match modifier.operation {
-                ModifierOperation::Absolute => {
-                    absolute_modifiers.push(modifier.clone());
-                }
-                ModifierOperation::Percentage => {
-                    percentage_modifiers.push(modifier.clone());
-                }
-                ModifierOperation::Override => {
-                    override_modifiers.push(modifier.clone());
-                }
-            }
-        }
+    pub(crate) fn set_base_value_without_recalculate(&mut self, v: f32) {
+        self.base_value = v;
+    }
 
-        for modifier in absolute_modifiers.clone().iter() {
-            self.apply_modifier(modifier);
+    pub(crate) fn add_modifier_without_recalculate(
+        &mut self,
+        modifier: &AttributeModifier,
+    ) -> bool {
+        if self.modifiers.contains(modifier) {
+            return false;
         }
 
-        for modifier in percentage_modifiers.clone().iter() {
-            self.apply_modifier(modifier);
-        }
+        self.modifiers.push(modifier.clone());
+        true
+    }
 
-        if !override_modifiers.is_empty() {
-            override_modifiers.sort_by(|a, b| b.priority.cmp(&a.priority));
+    pub(crate) fn insert_modifier_without_recalculate(&mut self, modifier: &AttributeModifier) {
+        self.unique_modifiers
+            .insert(modifier.source_id.clone(), modifier.clone());
+    }
 
-            self.apply_modifier(&override_modifiers[0]);
+    pub(crate) fn remove_modifier_without_recalculate(
+        &mut self,
+        modifier: &AttributeModifier,
+    ) -> bool {
+        match self.modifiers.iter().position(|v| v.uuid == modifier.uuid) {
+            Some(index) => {
+                self.modifiers.remove(index);
+                true
+            }
+            None => false,
         }
+    }
 
-        let current_value = self.current_value;
+    fn modifiers(&self) -> impl Iterator<Item = &AttributeModifier> {
+        self.modifiers.iter().chain(self.unique_modifiers.values())
+    }
 
-        self.set_current_value(current_value);
+    ///重新计算当前值，依次应用绝对值、百分比和优先级最高的覆盖更改器
+    pub fn recalculate_current_value(&mut self) {
+        self.set_current_value(self.calculated_value());
     }
 
-    fn apply_modifier(&mut self, modifier: &AttributeModifier) {
-        match modifier.operation {
-            ModifierOperation::Absolute => {
-                self.current_value += modifier.value;
-            }
-            ModifierOperation::Override => {
-                self.current_value = modifier.value;
+    ///基础值应用更改器之后的值，不修改当前值
+    pub fn calculated_value(&self) -> f32 {
+        let mut current_value = self.base_value;
+
+        for modifier in self.modifiers() {
+            if modifier.operation == ModifierOperation::Absolute {
+                current_value += modifier.value;
             }
-            ModifierOperation::Percentage => {
-                self.current_value += self.base_value * modifier.value;
+        }
+
+        for modifier in self.modifiers() {
+            if modifier.operation == ModifierOperation::Percentage {
+                current_value += self.base_value * modifier.value;
             }
         }
+
+        let override_modifier = self
+            .modifiers()
+            .filter(|v| v.operation == ModifierOperation::Override)
+            .reduce(|a, b| if b.priority > a.priority { b } else { a });
+
+        if let Some(modifier) = override_modifier {
+            current_value = modifier.value;
+        }
+
+        current_value
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ModifierOperation {
     Absolute,
     Override,
     Percentage,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct ModifierPriority(usize);
 
 impl ModifierPriority {
@@ -144,13 +168,13 @@ impl Default for ModifierPriority {
 }
 
 ///属性更改器
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AttributeModifier {
     pub operation: ModifierOperation,
     pub value: f32,
     pub uuid: Uuid,
     pub source_id: String,
-    pub attribute_name: String,
+    pub attribute_name: AttributeId,
     pub priority: ModifierPriority,
 }
 
@@ -160,7 +184,7 @@ impl AttributeModifier {
             operation: ModifierOperation::Absolute,
             value: 0.0,
             uuid: Uuid::new_v4(),
-            attribute_name: "".into(),
+            attribute_name: Default::default(),
             source_id: "".into(),
             priority: Default::default(),
         }
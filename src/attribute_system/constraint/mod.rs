@@ -1,6 +1,7 @@
 use bevy::platform::collections::HashMap;
+use serde::{Deserialize, Serialize};
 
-use crate::Attribute;
+use crate::{Attribute, AttributeId};
 
 pub struct AttributeConstraintProcessorContainer(
     HashMap<String, Box<dyn AttributeConstraintProcessor>>,
@@ -11,6 +12,10 @@ impl AttributeConstraintProcessorContainer {
         let mut container = Self::empty();
 
         container.add_processor("max", MaxAttributeConstraintProcessor);
+        container.add_processor("min", MinAttributeConstraintProcessor);
+        container.add_processor("ratio", RatioAttributeConstraintProcessor);
+        container.add_processor("sum", SumAttributeConstraintProcessor);
+        container.add_processor("step", StepAttributeConstraintProcessor);
 
         container
     }
@@ -37,10 +42,12 @@ impl Default for AttributeConstraintProcessorContainer {
 ///属性约束处理器
 pub trait AttributeConstraintProcessor: 'static + Sync + Send {
     //在更新属性之后，对被影响的属性进行更新
+    //value 为约束属性的新值，previous_value 为约束属性更新之前的值
     fn on_after_update_attribute_current_value(
         &self,
         attribute: &mut Attribute,
         value: f32,
+        previous_value: f32,
         constraint: &AttributeConstraint,
     );
 
@@ -55,18 +62,39 @@ pub trait AttributeConstraintProcessor: 'static + Sync + Send {
     }
 }
 
+///目标属性不能大于约束属性，且不小于下限
+///
+///参数 offset 为上限相对约束属性的偏移，默认为 0
+///参数 floor 为目标属性的下限，默认为 0
 pub struct MaxAttributeConstraintProcessor;
 
+impl MaxAttributeConstraintProcessor {
+    fn get_max_value(value: f32, constraint: &AttributeConstraint) -> f32 {
+        value + constraint.get_number("offset").unwrap_or(0.0)
+    }
+
+    fn get_floor(constraint: &AttributeConstraint) -> f32 {
+        constraint.get_number("floor").unwrap_or(0.0)
+    }
+}
+
 impl AttributeConstraintProcessor for MaxAttributeConstraintProcessor {
     fn on_after_update_attribute_current_value(
         &self,
         attribute: &mut Attribute,
         value: f32,
-        _constraint: &AttributeConstraint,
+        previous_value: f32,
+        constraint: &AttributeConstraint,
     ) {
-        attribute.recalculate_current_value();
-        let cul_value = attribute.get_current_value();
-        let value = cul_value.clamp(0.0, value);
+        let mut cul_value = attribute.get_current_value();
+
+        //只恢复之前被上限截断的部分，已经受到的伤害不会因为上限变化而恢复
+        if cul_value >= Self::get_max_value(previous_value, constraint) {
+            cul_value = cul_value.max(attribute.calculated_value());
+        }
+
+        let max_value = Self::get_max_value(value, constraint);
+        let value = cul_value.min(max_value).max(Self::get_floor(constraint));
         attribute.set_current_value(value);
     }
 
@@ -74,18 +102,257 @@ impl AttributeConstraintProcessor for MaxAttributeConstraintProcessor {
         &self,
         attribute: &mut Attribute,
         value: f32,
-        _constraint: &AttributeConstraint,
+        constraint: &AttributeConstraint,
+    ) -> f32 {
+        let max_value = Self::get_max_value(attribute.get_current_value(), constraint);
+
+        value.min(max_value).max(Self::get_floor(constraint))
+    }
+}
+
+///目标属性不能小于约束属性
+///
+///参数 offset 为下限相对约束属性的偏移，默认为 0
+pub struct MinAttributeConstraintProcessor;
+
+impl MinAttributeConstraintProcessor {
+    fn get_min_value(value: f32, constraint: &AttributeConstraint) -> f32 {
+        value + constraint.get_number("offset").unwrap_or(0.0)
+    }
+}
+
+impl AttributeConstraintProcessor for MinAttributeConstraintProcessor {
+    fn on_after_update_attribute_current_value(
+        &self,
+        attribute: &mut Attribute,
+        value: f32,
+        previous_value: f32,
+        constraint: &AttributeConstraint,
+    ) {
+        let mut cul_value = attribute.get_current_value();
+
+        //只恢复之前被下限截断的部分
+        if cul_value <= Self::get_min_value(previous_value, constraint) {
+            cul_value = cul_value.min(attribute.calculated_value());
+        }
+
+        attribute.set_current_value(cul_value.max(Self::get_min_value(value, constraint)));
+    }
+
+    fn on_before_update_attribute_current_value(
+        &self,
+        attribute: &mut Attribute,
+        value: f32,
+        constraint: &AttributeConstraint,
+    ) -> f32 {
+        value.max(Self::get_min_value(
+            attribute.get_current_value(),
+            constraint,
+        ))
+    }
+}
+
+///约束属性变化时，目标属性保持原有的比例
+///
+///参数 factor 为目标属性上限与约束属性的比值，默认为 1
+pub struct RatioAttributeConstraintProcessor;
+
+impl RatioAttributeConstraintProcessor {
+    fn get_factor(constraint: &AttributeConstraint) -> f32 {
+        constraint.get_number("factor").unwrap_or(1.0)
+    }
+}
+
+impl AttributeConstraintProcessor for RatioAttributeConstraintProcessor {
+    fn on_after_update_attribute_current_value(
+        &self,
+        attribute: &mut Attribute,
+        value: f32,
+        previous_value: f32,
+        constraint: &AttributeConstraint,
+    ) {
+        let factor = Self::get_factor(constraint);
+        let max_value = (value * factor).max(0.0);
+        let previous_max_value = previous_value * factor;
+
+        let cul_value = attribute.get_current_value();
+
+        let value = if previous_max_value > 0.0 {
+            cul_value / previous_max_value * max_value
+        } else {
+            cul_value
+        };
+
+        attribute.set_current_value(value.max(0.0).min(max_value));
+    }
+
+    fn on_before_update_attribute_current_value(
+        &self,
+        attribute: &mut Attribute,
+        value: f32,
+        constraint: &AttributeConstraint,
+    ) -> f32 {
+        let max_value = attribute.get_current_value() * Self::get_factor(constraint);
+
+        value.min(max_value)
+    }
+}
+
+///目标属性与约束属性之和不能大于参数 total
+pub struct SumAttributeConstraintProcessor;
+
+impl AttributeConstraintProcessor for SumAttributeConstraintProcessor {
+    fn on_after_update_attribute_current_value(
+        &self,
+        attribute: &mut Attribute,
+        value: f32,
+        _previous_value: f32,
+        constraint: &AttributeConstraint,
+    ) {
+        let Some(total) = constraint.get_number("total") else {
+            return;
+        };
+
+        let cul_value = attribute.get_current_value();
+        attribute.set_current_value(cul_value.min(total - value));
+    }
+
+    fn on_before_update_attribute_current_value(
+        &self,
+        attribute: &mut Attribute,
+        value: f32,
+        constraint: &AttributeConstraint,
     ) -> f32 {
+        let Some(total) = constraint.get_number("total") else {
+            return value;
+        };
+
+        value.min(total - attribute.get_current_value())
+    }
+}
+
+///目标属性取整为步长的整数倍
+///
+///步长为参数 step，没有设置时使用约束属性的值
+///参数 round 为 true 时四舍五入，否则向下取整
+pub struct StepAttributeConstraintProcessor;
+
+impl StepAttributeConstraintProcessor {
+    fn snap(value: f32, step: f32, constraint: &AttributeConstraint) -> f32 {
+        if step <= 0.0 {
+            return value;
+        }
+
+        if constraint.get_bool("round").unwrap_or(false) {
+            (value / step).round() * step
+        } else {
+            (value / step).floor() * step
+        }
+    }
+}
+
+impl AttributeConstraintProcessor for StepAttributeConstraintProcessor {
+    fn on_after_update_attribute_current_value(
+        &self,
+        attribute: &mut Attribute,
+        value: f32,
+        _previous_value: f32,
+        constraint: &AttributeConstraint,
+    ) {
+        let step = constraint.get_number("step").unwrap_or(value);
         let cul_value = attribute.get_current_value();
+        attribute.set_current_value(Self::snap(cul_value, step, constraint));
+    }
+
+    fn on_before_update_attribute_current_value(
+        &self,
+        attribute: &mut Attribute,
+        value: f32,
+        constraint: &AttributeConstraint,
+    ) -> f32 {
+        let step = constraint
+            .get_number("step")
+            .unwrap_or(attribute.get_current_value());
+
+        Self::snap(value, step, constraint)
+    }
+}
+
+///属性约束参数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AttributeConstraintParameter {
+    Number(f32),
+    Bool(bool),
+}
 
-        value.min(cul_value)
+impl From<f32> for AttributeConstraintParameter {
+    fn from(value: f32) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<bool> for AttributeConstraintParameter {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
     }
 }
 
 ///属性约束
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+///attribute_name 为约束属性，target_attribute_name 为被约束的属性
+///parameters 会传递给约束处理器，同一个处理器可以根据参数服务于不同的约束
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AttributeConstraint {
-    pub attribute_name: String,
-    pub target_attribute_name: String,
+    pub attribute_name: AttributeId,
+    pub target_attribute_name: AttributeId,
     pub constraint_name: String,
+    #[serde(with = "crate::serialize::sorted_map")]
+    pub parameters: HashMap<String, AttributeConstraintParameter>,
+}
+
+impl AttributeConstraint {
+    pub fn new(
+        attribute_name: impl Into<AttributeId>,
+        target_attribute_name: impl Into<AttributeId>,
+        constraint_name: &str,
+    ) -> Self {
+        Self {
+            attribute_name: attribute_name.into(),
+            target_attribute_name: target_attribute_name.into(),
+            constraint_name: constraint_name.to_string(),
+            parameters: Default::default(),
+        }
+    }
+
+    pub fn with_parameter(
+        mut self,
+        key: &str,
+        value: impl Into<AttributeConstraintParameter>,
+    ) -> Self {
+        self.set_parameter(key, value);
+        self
+    }
+
+    pub fn set_parameter(&mut self, key: &str, value: impl Into<AttributeConstraintParameter>) {
+        self.parameters.insert(key.to_string(), value.into());
+    }
+
+    pub fn get_parameter(&self, key: &str) -> Option<&AttributeConstraintParameter> {
+        self.parameters.get(key)
+    }
+
+    pub fn get_number(&self, key: &str) -> Option<f32> {
+        match self.parameters.get(key) {
+            Some(AttributeConstraintParameter::Number(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.parameters.get(key) {
+            Some(AttributeConstraintParameter::Bool(value)) => Some(*value),
+            _ => None,
+        }
+    }
 }
@@ -1,14 +1,20 @@
 mod attribute;
 mod constraint;
+mod transaction;
 
 pub use attribute::*;
 pub use constraint::*;
+pub use transaction::*;
 
 use bevy::platform::collections::HashMap;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default)]
+use crate::IdLookup;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AttributeSet {
-    attributes: HashMap<String, Attribute>,
+    #[serde(with = "crate::serialize::sorted_map")]
+    attributes: HashMap<AttributeId, Attribute>,
     constraints: Vec<AttributeConstraint>,
 }
 
@@ -20,46 +26,72 @@ impl AttributeSet {
     }
 
     pub fn insert_attribute(&mut self, attribute: Attribute) {
-        self.attributes
-            .insert(attribute.attribute_name.clone(), attribute);
+        self.attributes.insert(attribute.attribute_name, attribute);
+    }
+
+    pub fn contains(&self, attribute_name: impl IdLookup<AttributeId>) -> bool {
+        self.get(attribute_name).is_some()
+    }
+
+    pub fn get(&self, attribute_name: impl IdLookup<AttributeId>) -> Option<&Attribute> {
+        self.attributes.get(&attribute_name.lookup()?)
+    }
+
+    ///直接获取属性的可变引用，对属性的修改不会经过约束处理器
+    pub fn get_mut(
+        &mut self,
+        attribute_name: impl IdLookup<AttributeId>,
+    ) -> Option<&mut Attribute> {
+        self.attributes.get_mut(&attribute_name.lookup()?)
+    }
+
+    pub fn get_current_value(&self, attribute_name: impl IdLookup<AttributeId>) -> Option<f32> {
+        self.get(attribute_name).map(|v| v.get_current_value())
+    }
+
+    pub fn get_base_value(&self, attribute_name: impl IdLookup<AttributeId>) -> Option<f32> {
+        self.get(attribute_name).map(|v| v.get_base_value())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Attribute> {
+        self.attributes.values()
+    }
+
+    pub fn constraints(&self) -> &[AttributeConstraint] {
+        &self.constraints
     }
 
     ///在实际更新属性之前，将所要改变的值更改为合理的值
     fn on_before_update_attribute_current_value(
         &mut self,
-        attribute_name: &str,
+        attribute_name: AttributeId,
         value: f32,
         container: &AttributeConstraintProcessorContainer,
     ) -> f32 {
-        let index = match self
+        let mut value = value;
+
+        for constraint in self
             .constraints
             .iter()
-            .position(|v| v.target_attribute_name == attribute_name)
+            .filter(|v| v.target_attribute_name == attribute_name)
         {
-            Some(index) => index,
-            None => {
-                return value;
+            if let Some(processor) = container.get_processor(&constraint.constraint_name)
+                && let Some(attribute) = self.attributes.get_mut(&constraint.attribute_name)
+            {
+                value = processor
+                    .on_before_update_attribute_current_value(attribute, value, constraint);
             }
-        };
-
-        let constraint = &self.constraints[index];
-
-        if let Some(processor) = container.get_processor(&constraint.constraint_name) {
-            if let Some(attribute) = self.attributes.get_mut(&constraint.attribute_name) {
-                processor.on_before_update_attribute_current_value(attribute, value, constraint)
-            } else {
-                value
-            }
-        } else {
-            value
         }
+
+        value
     }
 
     ///在更新属性之后，对被影响的属性进行更新
     fn on_after_update_attribute_current_value(
         &mut self,
-        attribute_name: &str,
+        attribute_name: AttributeId,
         value: f32,
+        previous_value: f32,
         container: &AttributeConstraintProcessorContainer,
     ) {
         for constraint in self
@@ -67,68 +99,100 @@ impl AttributeSet {
             .iter()
             .filter(|v| v.attribute_name == attribute_name)
         {
-            if let Some(processor) = container.get_processor(&constraint.constraint_name) {
-                if let Some(attribute) = self.attributes.get_mut(&constraint.target_attribute_name)
-                {
-                    processor.on_after_update_attribute_current_value(attribute, value, constraint);
-                }
+            if let Some(processor) = container.get_processor(&constraint.constraint_name)
+                && let Some(attribute) = self.attributes.get_mut(&constraint.target_attribute_name)
+            {
+                processor.on_after_update_attribute_current_value(
+                    attribute,
+                    value,
+                    previous_value,
+                    constraint,
+                );
             }
         }
     }
 
     fn update_attribute_current_value(
         &mut self,
-        attribute_name: &str,
+        attribute_name: AttributeId,
         value: f32,
+        previous_value: f32,
         container: &AttributeConstraintProcessorContainer,
     ) {
         let final_value =
             self.on_before_update_attribute_current_value(attribute_name, value, container);
 
-        if final_value != value {
-            if let Some(attribute) = self.attributes.get_mut(attribute_name) {
-                attribute.set_current_value(final_value);
-            }
+        if let Some(attribute) = self.attributes.get_mut(&attribute_name) {
+            attribute.set_current_value(final_value);
         }
 
-        if let Some(current_value) = self
-            .attributes
-            .get(attribute_name)
-            .map(|v| v.get_current_value())
-        {
-            self.on_after_update_attribute_current_value(attribute_name, current_value, container);
+        if let Some(current_value) = self.get_current_value(attribute_name) {
+            self.on_after_update_attribute_current_value(
+                attribute_name,
+                current_value,
+                previous_value,
+                container,
+            );
         }
     }
 
     pub fn update_attribute_base_value(
         &mut self,
-        attribute_name: &str,
+        attribute_name: impl IdLookup<AttributeId>,
         value: f32,
         container: &AttributeConstraintProcessorContainer,
     ) {
-        let Some(current_value) = self
-            .attributes
-            .get(attribute_name)
-            .map(|v| v.get_current_value())
-        else {
+        let Some(attribute_name) = attribute_name.lookup() else {
             return;
         };
 
-        self.attributes
-            .get_mut(attribute_name)
-            .unwrap()
-            .set_base_value(value);
+        let Some(attribute) = self.attributes.get_mut(&attribute_name) else {
+            return;
+        };
 
-        let final_value = self
-            .attributes
-            .get(attribute_name)
-            .unwrap()
-            .get_current_value();
+        let current_value = attribute.get_current_value();
+        attribute.set_base_value(value);
+        let final_value = attribute.get_current_value();
 
         if final_value != current_value {
-            self.update_attribute_current_value(attribute_name, final_value, container);
+            self.update_attribute_current_value(
+                attribute_name,
+                final_value,
+                current_value,
+                container,
+            );
         }
     }
+
+    ///修改属性的当前值，不影响基础值，返回最终的当前值
+    ///
+    ///修改会经过约束处理器，例如伤害和治疗只修改当前生命值
+    pub fn set_current_value(
+        &mut self,
+        attribute_name: impl IdLookup<AttributeId>,
+        value: f32,
+        container: &AttributeConstraintProcessorContainer,
+    ) -> Option<f32> {
+        let attribute_name = attribute_name.lookup()?;
+        let current_value = self.get_current_value(attribute_name)?;
+
+        self.update_attribute_current_value(attribute_name, value, current_value, container);
+
+        self.get_current_value(attribute_name)
+    }
+
+    ///在属性的当前值上增加 delta，返回最终的当前值
+    pub fn apply_delta(
+        &mut self,
+        attribute_name: impl IdLookup<AttributeId>,
+        delta: f32,
+        container: &AttributeConstraintProcessorContainer,
+    ) -> Option<f32> {
+        let attribute_name = attribute_name.lookup()?;
+        let current_value = self.get_current_value(attribute_name)?;
+
+        self.set_current_value(attribute_name, current_value + delta, container)
+    }
 }
 
 #[cfg(test)]
@@ -141,20 +205,16 @@ mod test {
         let mut set = AttributeSet::default();
 
         let mut power = Attribute::default();
-        power.attribute_name = "power".to_string();
+        power.attribute_name = "power".into();
         power.set_base_value(10.0);
         set.insert_attribute(power);
 
         let mut max_power = Attribute::default();
-        max_power.attribute_name = "max_power".to_string();
+        max_power.attribute_name = "max_power".into();
         max_power.set_base_value(30.0);
         set.insert_attribute(max_power);
 
-        set.insert_constraint(AttributeConstraint {
-            attribute_name: "max_power".to_string(),
-            target_attribute_name: "power".to_string(),
-            constraint_name: "max".to_string(),
-        });
+        set.insert_constraint(AttributeConstraint::new("max_power", "power", "max"));
 
         set
     }
@@ -167,16 +227,126 @@ mod test {
 
         set.update_attribute_base_value("power", 40.0, &container);
 
-        assert_eq!(
-            30.0,
-            set.attributes.get("power").unwrap().get_current_value()
-        );
+        assert_eq!(30.0, set.get_current_value("power").unwrap());
 
         set.update_attribute_base_value("max_power", 35.0, &container);
 
-        assert_eq!(
-            35.0,
-            set.attributes.get("power").unwrap().get_current_value()
+        assert_eq!(35.0, set.get_current_value("power").unwrap());
+    }
+
+    #[test]
+    fn test_attribute_set_current_value() {
+        let mut set = new_attribute_set();
+
+        let container = AttributeConstraintProcessorContainer::new();
+
+        assert_eq!(Some(30.0), set.apply_delta("power", 50.0, &container));
+        assert_eq!(Some(25.0), set.apply_delta("power", -5.0, &container));
+        assert_eq!(Some(10.0), set.get_base_value("power"));
+
+        assert_eq!(Some(5.0), set.set_current_value("power", 5.0, &container));
+        assert_eq!(None, set.apply_delta("unknown", 5.0, &container));
+
+        assert_eq!(2, set.iter().count());
+    }
+
+    #[test]
+    fn test_constraint_keeps_current_value() {
+        let container = AttributeConstraintProcessorContainer::new();
+        let target_value = |set: &AttributeSet| set.get_current_value("target").unwrap();
+
+        //受到伤害之后改变上限，不会恢复当前值
+        let mut set =
+            new_constraint_attribute_set(AttributeConstraint::new("source", "target", "max"));
+        set.apply_delta("target", -20.0, &container);
+        set.update_attribute_base_value("source", 120.0, &container);
+        assert_eq!(30.0, target_value(&set));
+        set.update_attribute_base_value("source", 20.0, &container);
+        assert_eq!(20.0, target_value(&set));
+
+        //提高下限之后再降低，只恢复被下限截断的部分
+        let mut set =
+            new_constraint_attribute_set(AttributeConstraint::new("source", "target", "min"));
+        set.update_attribute_base_value("source", 30.0, &container);
+        set.apply_delta("target", 20.0, &container);
+        set.update_attribute_base_value("source", 80.0, &container);
+        assert_eq!(80.0, target_value(&set));
+        set.update_attribute_base_value("source", 10.0, &container);
+        assert_eq!(50.0, target_value(&set));
+    }
+
+    fn new_constraint_attribute_set(constraint: AttributeConstraint) -> AttributeSet {
+        let mut set = AttributeSet::default();
+
+        for (name, value) in [("source", 100.0), ("target", 50.0)] {
+            let mut attribute = Attribute::default();
+            attribute.attribute_name = name.into();
+            attribute.set_base_value(value);
+            set.insert_attribute(attribute);
+        }
+
+        set.insert_constraint(constraint);
+
+        set
+    }
+
+    #[test]
+    fn test_attribute_constraint_processors() {
+        let container = AttributeConstraintProcessorContainer::new();
+        let target_value = |set: &AttributeSet| set.get_current_value("target").unwrap();
+
+        let mut set =
+            new_constraint_attribute_set(AttributeConstraint::new("source", "target", "min"));
+        set.update_attribute_base_value("target", 80.0, &container);
+        assert_eq!(100.0, target_value(&set));
+
+        let mut set =
+            new_constraint_attribute_set(AttributeConstraint::new("source", "target", "ratio"));
+        set.update_attribute_base_value("source", 200.0, &container);
+        assert_eq!(100.0, target_value(&set));
+        set.update_attribute_base_value("source", 50.0, &container);
+        assert_eq!(25.0, target_value(&set));
+
+        let mut set = new_constraint_attribute_set(
+            AttributeConstraint::new("source", "target", "ratio").with_parameter("factor", 0.5),
+        );
+        set.update_attribute_base_value("target", 70.0, &container);
+        assert_eq!(50.0, target_value(&set));
+
+        let mut set = new_constraint_attribute_set(
+            AttributeConstraint::new("source", "target", "sum").with_parameter("total", 120.0),
+        );
+        set.update_attribute_base_value("target", 40.0, &container);
+        assert_eq!(20.0, target_value(&set));
+
+        let mut set = new_constraint_attribute_set(
+            AttributeConstraint::new("source", "target", "step").with_parameter("step", 15.0),
+        );
+        set.update_attribute_base_value("target", 40.0, &container);
+        assert_eq!(30.0, target_value(&set));
+    }
+
+    #[test]
+    fn test_attribute_constraint_parameters() {
+        let container = AttributeConstraintProcessorContainer::new();
+        let target_value = |set: &AttributeSet| set.get_current_value("target").unwrap();
+
+        let mut set = new_constraint_attribute_set(
+            AttributeConstraint::new("source", "target", "max")
+                .with_parameter("offset", -20.0)
+                .with_parameter("floor", 10.0),
+        );
+        set.update_attribute_base_value("target", 90.0, &container);
+        assert_eq!(80.0, target_value(&set));
+        set.update_attribute_base_value("target", 5.0, &container);
+        assert_eq!(10.0, target_value(&set));
+
+        let mut set = new_constraint_attribute_set(
+            AttributeConstraint::new("source", "target", "step")
+                .with_parameter("step", 15.0)
+                .with_parameter("round", true),
         );
+        set.update_attribute_base_value("target", 40.0, &container);
+        assert_eq!(45.0, target_value(&set));
     }
 }
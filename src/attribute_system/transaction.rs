@@ -0,0 +1,321 @@
+use super::{AttributeConstraintProcessorContainer, AttributeId, AttributeModifier, AttributeSet};
+
+///事务中记录的属性操作
+#[derive(Debug, Clone)]
+pub enum AttributeOperation {
+    AddModifier(AttributeModifier),
+    InsertModifier(AttributeModifier),
+    RemoveModifier(AttributeModifier),
+    SetBaseValue {
+        attribute_name: AttributeId,
+        value: f32,
+    },
+    ApplyDelta {
+        attribute_name: AttributeId,
+        delta: f32,
+    },
+}
+
+impl AttributeOperation {
+    pub fn attribute_name(&self) -> AttributeId {
+        match self {
+            AttributeOperation::AddModifier(modifier)
+            | AttributeOperation::InsertModifier(modifier)
+            | AttributeOperation::RemoveModifier(modifier) => modifier.attribute_name,
+            AttributeOperation::SetBaseValue { attribute_name, .. }
+            | AttributeOperation::ApplyDelta { attribute_name, .. } => *attribute_name,
+        }
+    }
+}
+
+///属性事务错误
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttributeTransactionError {
+    ///操作的属性不在属性集中
+    UnknownAttribute(AttributeId),
+}
+
+///属性事务
+///
+///事务中的修改在提交之前不会影响属性集，提交时每个属性只重新计算一次，约束也只传播一次
+///没有提交的事务在丢弃时自动回滚
+pub struct AttributeTransaction<'a> {
+    set: &'a mut AttributeSet,
+    operations: Vec<AttributeOperation>,
+}
+
+impl<'a> AttributeTransaction<'a> {
+    pub fn new(set: &'a mut AttributeSet) -> Self {
+        Self {
+            set,
+            operations: vec![],
+        }
+    }
+
+    pub fn operations(&self) -> &[AttributeOperation] {
+        &self.operations
+    }
+
+    pub fn push(&mut self, operation: AttributeOperation) -> &mut Self {
+        self.operations.push(operation);
+        self
+    }
+
+    ///添加更改器，更改器作用的属性为 modifier.attribute_name
+    pub fn add_modifier(&mut self, modifier: &AttributeModifier) -> &mut Self {
+        self.push(AttributeOperation::AddModifier(modifier.clone()))
+    }
+
+    pub fn insert_modifier(&mut self, modifier: &AttributeModifier) -> &mut Self {
+        self.push(AttributeOperation::InsertModifier(modifier.clone()))
+    }
+
+    pub fn remove_modifier(&mut self, modifier: &AttributeModifier) -> &mut Self {
+        self.push(AttributeOperation::RemoveModifier(modifier.clone()))
+    }
+
+    pub fn set_base_value(
+        &mut self,
+        attribute_name: impl Into<AttributeId>,
+        value: f32,
+    ) -> &mut Self {
+        self.push(AttributeOperation::SetBaseValue {
+            attribute_name: attribute_name.into(),
+            value,
+        })
+    }
+
+    pub fn apply_delta(&mut self, attribute_name: impl Into<AttributeId>, delta: f32) -> &mut Self {
+        self.push(AttributeOperation::ApplyDelta {
+            attribute_name: attribute_name.into(),
+            delta,
+        })
+    }
+
+    ///放弃事务中的所有修改
+    pub fn rollback(self) {}
+
+    ///提交事务
+    ///
+    ///先应用所有更改器和基础值，再统一重新计算并传播约束，最后应用当前值的增量
+    ///
+    ///有操作的属性不在属性集中时不做任何修改并返回错误
+    pub fn commit(
+        self,
+        container: &AttributeConstraintProcessorContainer,
+    ) -> Result<(), AttributeTransactionError> {
+        let AttributeTransaction { set, operations } = self;
+
+        if let Some(operation) = operations
+            .iter()
+            .find(|operation| !set.contains(operation.attribute_name()))
+        {
+            return Err(AttributeTransactionError::UnknownAttribute(
+                operation.attribute_name(),
+            ));
+        }
+
+        let mut recalculated: Vec<(AttributeId, f32)> = vec![];
+        let mut deltas: Vec<(AttributeId, f32)> = vec![];
+
+        for operation in operations.iter() {
+            let attribute_name = operation.attribute_name();
+
+            let Some(attribute) = set.attributes.get_mut(&attribute_name) else {
+                continue;
+            };
+
+            let previous_value = attribute.get_current_value();
+
+            match operation {
+                AttributeOperation::AddModifier(modifier) => {
+                    attribute.add_modifier_without_recalculate(modifier);
+                }
+                AttributeOperation::InsertModifier(modifier) => {
+                    attribute.insert_modifier_without_recalculate(modifier);
+                }
+                AttributeOperation::RemoveModifier(modifier) => {
+                    attribute.remove_modifier_without_recalculate(modifier);
+                }
+                AttributeOperation::SetBaseValue { value, .. } => {
+                    attribute.set_base_value_without_recalculate(*value);
+                }
+                AttributeOperation::ApplyDelta { delta, .. } => {
+                    match deltas.iter_mut().find(|(name, _)| *name == attribute_name) {
+                        Some((_, total)) => *total += delta,
+                        None => deltas.push((attribute_name, *delta)),
+                    }
+                    continue;
+                }
+            }
+
+            if !recalculated.iter().any(|(name, _)| *name == attribute_name) {
+                recalculated.push((attribute_name, previous_value));
+            }
+        }
+
+        for (attribute_name, _) in recalculated.iter() {
+            if let Some(attribute) = set.attributes.get_mut(attribute_name) {
+                attribute.recalculate_current_value();
+            }
+        }
+
+        for (attribute_name, previous_value) in recalculated {
+            if let Some(current_value) = set.get_current_value(attribute_name)
+                && current_value != previous_value
+            {
+                set.update_attribute_current_value(
+                    attribute_name,
+                    current_value,
+                    previous_value,
+                    container,
+                );
+            }
+        }
+
+        for (attribute_name, delta) in deltas {
+            set.apply_delta(attribute_name, delta, container);
+        }
+
+        Ok(())
+    }
+}
+
+impl AttributeSet {
+    ///开始一个事务
+    pub fn begin_transaction(&mut self) -> AttributeTransaction<'_> {
+        AttributeTransaction::new(self)
+    }
+
+    ///在事务中执行 f，返回 Ok 时提交，返回 Err 或者提交失败时回滚
+    pub fn transaction<T, E: From<AttributeTransactionError>>(
+        &mut self,
+        container: &AttributeConstraintProcessorContainer,
+        f: impl FnOnce(&mut AttributeTransaction) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let mut transaction = self.begin_transaction();
+
+        match f(&mut transaction) {
+            Ok(value) => {
+                transaction.commit(container)?;
+                Ok(value)
+            }
+            Err(e) => {
+                transaction.rollback();
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use crate::{
+        Attribute, AttributeConstraint, AttributeConstraintProcessor,
+        AttributeConstraintProcessorContainer, AttributeModifier, AttributeSet,
+        AttributeTransactionError,
+    };
+
+    ///记录约束传播的次数
+    struct CountAttributeConstraintProcessor(Arc<AtomicUsize>);
+
+    impl AttributeConstraintProcessor for CountAttributeConstraintProcessor {
+        fn on_after_update_attribute_current_value(
+            &self,
+            _attribute: &mut Attribute,
+            _value: f32,
+            _previous_value: f32,
+            _constraint: &AttributeConstraint,
+        ) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[derive(Debug)]
+    enum TestError {
+        SkillFailed,
+        Transaction(AttributeTransactionError),
+    }
+
+    impl From<AttributeTransactionError> for TestError {
+        fn from(value: AttributeTransactionError) -> Self {
+            TestError::Transaction(value)
+        }
+    }
+
+    fn new_attribute_set() -> AttributeSet {
+        let mut set = AttributeSet::default();
+
+        let mut health = Attribute::default();
+        health.attribute_name = "health".into();
+        health.set_base_value(100.0);
+        set.insert_attribute(health);
+
+        let mut max_health = Attribute::default();
+        max_health.attribute_name = "max_health".into();
+        max_health.set_base_value(100.0);
+        set.insert_attribute(max_health);
+
+        set.insert_constraint(AttributeConstraint::new("max_health", "health", "ratio"));
+
+        set
+    }
+
+    #[test]
+    fn test_attribute_transaction() {
+        let mut set = new_attribute_set();
+        let mut container = AttributeConstraintProcessorContainer::new();
+        let count = Arc::new(AtomicUsize::new(0));
+        container.add_processor("count", CountAttributeConstraintProcessor(count.clone()));
+        set.insert_constraint(AttributeConstraint::new("max_health", "health", "count"));
+
+        let result: Result<(), TestError> = set.transaction(&container, |transaction| {
+            for _ in 0..10 {
+                let mut modifier = AttributeModifier::empty();
+                modifier.attribute_name = "max_health".into();
+                modifier.value = 10.0;
+                transaction.add_modifier(&modifier);
+            }
+
+            transaction.apply_delta("health", -50.0);
+
+            Ok(())
+        });
+
+        assert!(result.is_ok());
+        assert_eq!(Some(200.0), set.get_current_value("max_health"));
+        assert_eq!(Some(150.0), set.get_current_value("health"));
+        //十个更改器只重新计算并传播一次
+        assert_eq!(1, count.load(Ordering::SeqCst));
+
+        let result: Result<(), TestError> = set.transaction(&container, |transaction| {
+            transaction.set_base_value("max_health", 10.0);
+            transaction.apply_delta("health", -100.0);
+
+            Err(TestError::SkillFailed)
+        });
+
+        assert!(matches!(result, Err(TestError::SkillFailed)));
+        assert_eq!(Some(200.0), set.get_current_value("max_health"));
+        assert_eq!(Some(150.0), set.get_current_value("health"));
+
+        //未知的属性使整个事务失败
+        let result: Result<(), TestError> = set.transaction(&container, |transaction| {
+            transaction.set_base_value("max_health", 10.0);
+            transaction.apply_delta("mana", -10.0);
+
+            Ok(())
+        });
+
+        assert!(matches!(
+            result,
+            Err(TestError::Transaction(AttributeTransactionError::UnknownAttribute(name))) if name == "mana"
+        ));
+        assert_eq!(Some(200.0), set.get_current_value("max_health"));
+    }
+}
@@ -0,0 +1,393 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{SkillCasting, SkillError, SkillInterrupt};
+
+use super::{Battle, BattleAction, CombatEvent, CombatantId};
+
+///单位正在蓄力或者引导的技能
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingSkill {
+    ///单位装备的技能的名称
+    pub skill_name: String,
+    pub target: CombatantId,
+    ///为 true 时为引导，否则为蓄力
+    pub channel: bool,
+    ///蓄力时为释放前剩余的回合数，引导时为剩余的释放次数
+    pub remaining_turns: u32,
+    ///开始之后受到的伤害
+    pub damage_taken: f32,
+    pub interrupt: SkillInterrupt,
+}
+
+impl Battle {
+    ///单位正在蓄力或者引导的技能
+    pub fn pending(&self, id: CombatantId) -> Option<&PendingSkill> {
+        self.get(id)
+            .and_then(|combatant| combatant.pending.as_ref())
+    }
+
+    ///使用眩晕等控制效果打断单位正在蓄力或者引导的技能，返回被打断的技能
+    ///
+    ///技能不能被打断时返回 None
+    pub fn interrupt(&mut self, id: CombatantId) -> Option<PendingSkill> {
+        let index = id.0 as usize;
+        let pending = self.combatants.get(index)?.pending.as_ref()?;
+
+        if pending.interrupt.uninterruptible {
+            return None;
+        }
+
+        self.cancel_pending(index)
+    }
+
+    fn cancel_pending(&mut self, index: usize) -> Option<PendingSkill> {
+        let pending = self.combatants[index].pending.take()?;
+
+        self.push_event(CombatEvent::SkillInterrupted {
+            caster: self.combatants[index].id,
+            skill_name: pending.skill_name.clone(),
+        });
+
+        Some(pending)
+    }
+
+    ///按照技能的释放方式开始释放技能，下标无效时返回 None
+    ///
+    ///开始时扣除技能的消耗并开始冷却，技能无法使用或者目标不满足技能的目标规则时不会行动
+    pub(super) fn start_skill(
+        &mut self,
+        index: usize,
+        skill_index: usize,
+        target_index: usize,
+    ) -> Result<Option<Vec<BattleAction>>, SkillError> {
+        let (Some(skill), Some(target)) = (
+            self.combatants[index].skill(skill_index),
+            self.combatants.get(target_index),
+        ) else {
+            return Ok(None);
+        };
+
+        let (skill, target) = (skill.clone(), target.id);
+
+        if !self.is_valid_target(self.combatants[index].id, &skill, target) {
+            self.push_event(CombatEvent::SkillRejected {
+                caster: self.combatants[index].id,
+                target,
+                skill_name: skill.skill_name,
+                reason: None,
+            });
+            return Ok(Some(vec![]));
+        }
+
+        if let Err(reason) = self.combatants[index]
+            .skill_component
+            .use_skill(&skill, &self.constraints)
+        {
+            self.push_event(CombatEvent::SkillRejected {
+                caster: self.combatants[index].id,
+                target,
+                skill_name: skill.skill_name,
+                reason: Some(reason),
+            });
+            return Ok(Some(vec![]));
+        }
+
+        let (turns, channel, interrupt) = match skill.casting {
+            SkillCasting::Charge { turns, interrupt } if turns > 0 => (turns, false, interrupt),
+            SkillCasting::Channel { turns, interrupt } if turns > 1 => (turns, true, interrupt),
+            _ => return self.perform(index, skill_index, target_index),
+        };
+
+        let pending = PendingSkill {
+            skill_name: skill.skill_name,
+            target,
+            channel,
+            remaining_turns: turns,
+            damage_taken: 0.0,
+            interrupt,
+        };
+
+        self.push_event(CombatEvent::SkillStarted {
+            caster: self.combatants[index].id,
+            target: pending.target,
+            skill_name: pending.skill_name.clone(),
+            turns,
+        });
+        self.combatants[index].pending = Some(pending);
+
+        if channel {
+            self.continue_skill(index)
+        } else {
+            Ok(Some(vec![]))
+        }
+    }
+
+    ///继续蓄力或者引导，蓄力结束或者引导时释放技能，目标不再满足目标规则或者卸下技能时打断
+    ///
+    ///技能被沉默等状态禁用时，可以被打断的技能被打断，不能被打断的技能暂停到状态结束
+    pub(super) fn continue_skill(
+        &mut self,
+        index: usize,
+    ) -> Result<Option<Vec<BattleAction>>, SkillError> {
+        let Some(pending) = self.combatants[index].pending.clone() else {
+            return Ok(None);
+        };
+
+        let target_index = pending.target.0 as usize;
+        let caster = &self.combatants[index];
+
+        let skill = caster.skills().enumerate().find(|(_, skill)| {
+            skill.skill_name == pending.skill_name
+                && self.is_valid_target(caster.id, skill, pending.target)
+        });
+
+        let Some((skill_index, skill)) = skill else {
+            self.cancel_pending(index);
+            return Ok(Some(vec![]));
+        };
+
+        if caster.skill_component.check_tags(skill).is_err() {
+            if !pending.interrupt.uninterruptible {
+                self.cancel_pending(index);
+            }
+
+            return Ok(Some(vec![]));
+        }
+
+        if !pending.channel {
+            let remaining_turns = pending.remaining_turns.saturating_sub(1);
+
+            if remaining_turns > 0 {
+                if let Some(pending) = self.combatants[index].pending.as_mut() {
+                    pending.remaining_turns = remaining_turns;
+                }
+
+                return Ok(Some(vec![]));
+            }
+
+            self.combatants[index].pending = None;
+
+            return self.perform(index, skill_index, target_index);
+        }
+
+        let actions = self.perform(index, skill_index, target_index)?;
+
+        //引导过程中可能被反击等效果打断
+        if let Some(pending) = self.combatants[index].pending.as_mut() {
+            pending.remaining_turns = pending.remaining_turns.saturating_sub(1);
+
+            if pending.remaining_turns == 0 {
+                self.combatants[index].pending = None;
+            }
+        }
+
+        Ok(actions)
+    }
+
+    ///记录单位受到的伤害，达到打断的阈值或者死亡时打断正在释放的技能
+    pub(super) fn record_damage_taken(&mut self, index: usize, damage: f32) {
+        let alive = self.combatants[index].is_alive();
+
+        let Some(pending) = self.combatants[index].pending.as_mut() else {
+            return;
+        };
+
+        pending.damage_taken += damage;
+
+        let interrupted = pending
+            .interrupt
+            .damage_threshold
+            .is_some_and(|threshold| pending.damage_taken >= threshold);
+
+        if interrupted || !alive {
+            self.cancel_pending(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        AttributeUtils, Battle, CombatEvent, CombatantId, DamageSkillEffectProcessor,
+        NumberGeneratorContainer, PatternBattleAi, PatternStep, Skill, SkillCasting,
+        SkillComponent, SkillEffect, SkillEffectProcessorContainer, SkillInterrupt, SkillStatus,
+        SkillTags, SkillUnavailable, SkillUsage, TargetSelector,
+    };
+
+    fn new_skill(skill_name: &str, damage: f32, casting: SkillCasting) -> Skill {
+        Skill::new(
+            skill_name,
+            vec![
+                SkillEffect::new("damage_skill_effect")
+                    .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, damage),
+            ],
+        )
+        .with_casting(casting)
+    }
+
+    fn new_battle(knight: Skill, ogre: Skill) -> Battle {
+        let mut processors = SkillEffectProcessorContainer::new();
+        processors.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+
+        let mut battle = Battle::new(processors, NumberGeneratorContainer::seeded(1));
+        battle.enable_log();
+
+        for (name, team, skill) in [("knight", 0, knight), ("ogre", 1, ogre)] {
+            battle.add_combatant(
+                name,
+                team,
+                SkillComponent::new(AttributeUtils::new_skill_attribute_set()),
+                vec![skill.clone()],
+                PatternBattleAi::new(vec![PatternStep {
+                    skill_name: skill.skill_name,
+                    selector: TargetSelector::First,
+                    ally: false,
+                }]),
+            );
+        }
+
+        battle
+    }
+
+    #[test]
+    fn test_charge_and_channel() {
+        let knight = CombatantId(0);
+        let ogre = CombatantId(1);
+
+        let charge = SkillCasting::Charge {
+            turns: 2,
+            interrupt: SkillInterrupt::default(),
+        };
+        let mut battle = new_battle(
+            new_skill("smash", 65.0, charge),
+            new_skill("idle", 0.0, SkillCasting::Instant),
+        );
+
+        //65 * 100 / 130 = 50
+        battle.run_turn().unwrap();
+        assert_eq!(Some(2), battle.pending(knight).map(|p| p.remaining_turns));
+        battle.run_turn().unwrap();
+        assert_eq!(Some(1), battle.pending(knight).map(|p| p.remaining_turns));
+        assert_eq!(100.0, battle.get(ogre).unwrap().health());
+        battle.run_turn().unwrap();
+        assert_eq!(None, battle.pending(knight));
+        assert_eq!(50.0, battle.get(ogre).unwrap().health());
+
+        let channel = SkillCasting::Channel {
+            turns: 3,
+            interrupt: SkillInterrupt::default(),
+        };
+        let mut battle = new_battle(
+            new_skill("beam", 13.0, channel),
+            new_skill("idle", 0.0, SkillCasting::Instant),
+        );
+
+        //13 * 100 / 130 = 10
+        for health in [90.0, 80.0, 70.0] {
+            battle.run_turn().unwrap();
+            assert_eq!(health, battle.get(ogre).unwrap().health());
+        }
+        assert_eq!(None, battle.pending(knight));
+
+        let interruptible = SkillCasting::Charge {
+            turns: 3,
+            interrupt: SkillInterrupt {
+                damage_threshold: Some(20.0),
+                uninterruptible: false,
+            },
+        };
+        let mut battle = new_battle(
+            new_skill("smash", 65.0, interruptible),
+            new_skill("punch", 13.0, SkillCasting::Instant),
+        );
+
+        battle.run_turn().unwrap();
+        assert!(battle.pending(knight).is_some());
+        battle.run_turn().unwrap();
+        assert!(battle.pending(knight).is_none());
+        assert!(battle.log().unwrap().iter().any(|entry| matches!(
+            &entry.event,
+            CombatEvent::SkillInterrupted { caster, .. } if *caster == knight
+        )));
+
+        battle.run_turn().unwrap();
+        assert!(battle.pending(knight).is_some());
+        assert!(battle.interrupt(knight).is_some());
+        assert!(battle.interrupt(knight).is_none());
+    }
+
+    #[test]
+    fn test_stun_and_silence() {
+        let knight = CombatantId(0);
+
+        let charge = |uninterruptible| SkillCasting::Charge {
+            turns: 2,
+            interrupt: SkillInterrupt {
+                damage_threshold: None,
+                uninterruptible,
+            },
+        };
+        let idle = new_skill("idle", 0.0, SkillCasting::Instant);
+
+        //眩晕打断蓄力，眩晕期间无法行动
+        let mut battle = new_battle(
+            new_skill("fireball", 65.0, charge(false)).with_tag(SkillTags::MAGIC),
+            idle.clone(),
+        );
+        battle.run_turn().unwrap();
+        assert!(battle.add_status(knight, SkillStatus::stun(Some(2))));
+        assert!(battle.pending(knight).is_none());
+
+        let actions = battle.run_turn().unwrap();
+        assert!(actions.iter().all(|action| action.caster != knight));
+        assert!(battle.pending(knight).is_none());
+
+        //不能被打断的技能不受眩晕影响，被沉默时暂停到沉默结束
+        let mut battle = new_battle(
+            new_skill("fireball", 65.0, charge(true)).with_tag(SkillTags::MAGIC),
+            idle,
+        );
+        battle.run_turn().unwrap();
+        assert!(battle.add_status(knight, SkillStatus::stun(Some(1))));
+        assert!(battle.add_status(knight, SkillStatus::silence(Some(2))));
+        assert_eq!(Some(2), battle.pending(knight).map(|p| p.remaining_turns));
+
+        battle.run_turn().unwrap();
+        assert_eq!(Some(2), battle.pending(knight).map(|p| p.remaining_turns));
+        battle.run_turn().unwrap();
+        assert_eq!(Some(1), battle.pending(knight).map(|p| p.remaining_turns));
+    }
+
+    #[test]
+    fn test_unavailable_skill_is_logged() {
+        let mut battle = new_battle(
+            new_skill("slash", 13.0, SkillCasting::Instant),
+            new_skill("idle", 0.0, SkillCasting::Instant),
+        );
+        battle.combatants[0]
+            .skill_component
+            .skill_book
+            .get_mut("slash")
+            .unwrap()
+            .usage = SkillUsage::default().with_cooldown(2);
+
+        assert_eq!(1, battle.start_skill(0, 0, 1).unwrap().unwrap().len());
+
+        //冷却中的技能不会释放，记录不能使用的原因
+        assert_eq!(Some(vec![]), battle.start_skill(0, 0, 1).unwrap());
+        assert_eq!(
+            Some(&CombatEvent::SkillRejected {
+                caster: CombatantId(0),
+                target: CombatantId(1),
+                skill_name: "slash".to_string(),
+                reason: Some(SkillUnavailable::Cooldown(2)),
+            }),
+            battle
+                .log()
+                .unwrap()
+                .entries()
+                .last()
+                .map(|entry| &entry.event)
+        );
+    }
+}
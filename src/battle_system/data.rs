@@ -0,0 +1,361 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AiWeights, Attribute, AttributeConstraintProcessorContainer, AttributeUtils, BattleAi,
+    CounterSkillEffectProcessor, DamageSkillEffectProcessor, HealSkillEffectProcessor,
+    LifestealSkillEffectProcessor, PatternBattleAi, PatternStep, RandomBattleAi,
+    ReflectSkillEffectProcessor, Skill, SkillBook, SkillCasting, SkillComponent, SkillEffect,
+    SkillEffectProcessorContainer, SkillProperty, SkillScaling, SkillTag, SkillTarget, SkillUsage,
+    SummonSkillEffectProcessor, TauntSkillEffectProcessor, ThornsSkillEffectProcessor,
+    UtilityBattleAi,
+};
+
+use super::{Battle, PassiveSkill, SkillTrigger, SummonTemplate, TriggerTarget};
+
+///技能效果数据
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkillEffectData {
+    pub name: String,
+    #[serde(default)]
+    pub payload: BTreeMap<String, SkillProperty>,
+    #[serde(default)]
+    pub scaling: BTreeMap<String, SkillScaling>,
+}
+
+///技能数据
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkillData {
+    pub skill_name: String,
+    #[serde(default)]
+    pub display_name: String,
+    pub effects: Vec<SkillEffectData>,
+    #[serde(default)]
+    pub casting: SkillCasting,
+    #[serde(default = "Skill::default_level")]
+    pub level: u32,
+    #[serde(default)]
+    pub tags: Vec<SkillTag>,
+    ///目标规则，被动技能数据的 target 为触发目标，所以使用 targeting
+    #[serde(default, rename = "targeting")]
+    pub target: SkillTarget,
+    ///冷却、充能和消耗
+    #[serde(flatten)]
+    pub usage: SkillUsage,
+}
+
+impl SkillData {
+    pub fn to_skill(&self) -> Skill {
+        let display_name = if self.display_name.is_empty() {
+            self.skill_name.clone()
+        } else {
+            self.display_name.clone()
+        };
+
+        Skill {
+            skill_name: self.skill_name.clone(),
+            display_name,
+            effcts: self
+                .effects
+                .iter()
+                .map(|effect| {
+                    let skill_effect = effect.payload.iter().fold(
+                        SkillEffect::new(effect.name.as_str()),
+                        |skill_effect, (key, value)| {
+                            skill_effect.with_value(key.as_str(), value.clone())
+                        },
+                    );
+
+                    effect
+                        .scaling
+                        .iter()
+                        .fold(skill_effect, |skill_effect, (key, scaling)| {
+                            skill_effect.with_scaling(key.as_str(), scaling.clone())
+                        })
+                })
+                .collect(),
+            casting: self.casting,
+            level: self.level,
+            tags: self.tags.clone(),
+            target: self.target,
+        }
+    }
+}
+
+///被动技能数据
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PassiveData {
+    #[serde(flatten)]
+    pub skill: SkillData,
+    pub trigger: SkillTrigger,
+    #[serde(default)]
+    pub target: TriggerTarget,
+    #[serde(default)]
+    pub source_tag: Option<SkillTag>,
+}
+
+impl PassiveData {
+    pub fn to_passive_skill(&self) -> PassiveSkill {
+        PassiveSkill {
+            skill: self.skill.to_skill(),
+            trigger: self.trigger.clone(),
+            target: self.target,
+            source_tag: self.source_tag,
+        }
+    }
+}
+
+///单位使用的 AI
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AiData {
+    #[default]
+    Random,
+    Utility {
+        #[serde(default)]
+        weights: AiWeights,
+    },
+    Pattern {
+        steps: Vec<PatternStep>,
+    },
+}
+
+impl AiData {
+    ///创建 AI，AI 使用的随机数由战斗的 ai_generators 提供
+    pub fn to_battle_ai(&self) -> Box<dyn BattleAi> {
+        match self {
+            AiData::Random => Box::new(RandomBattleAi::default()),
+            AiData::Utility { weights } => Box::new(UtilityBattleAi::new(weights.clone())),
+            AiData::Pattern { steps } => Box::new(PatternBattleAi::new(steps.clone())),
+        }
+    }
+}
+
+///单位数据
+///
+///attributes 中的值会覆盖 AttributeUtils::new_skill_attribute_set 中对应属性的基础值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnitData {
+    pub name: String,
+    #[serde(default)]
+    pub attributes: BTreeMap<String, f32>,
+    pub skills: Vec<SkillData>,
+    #[serde(default)]
+    pub passives: Vec<PassiveData>,
+    ///技能的装备栏数量，None 表示不限制，超出装备栏的技能无法使用
+    #[serde(default)]
+    pub skill_slots: Option<usize>,
+    #[serde(default)]
+    pub ai: AiData,
+}
+
+impl UnitData {
+    pub fn to_skill_component(
+        &self,
+        container: &AttributeConstraintProcessorContainer,
+    ) -> SkillComponent {
+        let mut attribute_set = AttributeUtils::new_skill_attribute_set();
+
+        for (name, value) in self.attributes.iter() {
+            if attribute_set.contains(name) {
+                attribute_set.update_attribute_base_value(name, *value, container);
+            } else {
+                let mut attribute = Attribute::default();
+                attribute.attribute_name = name.into();
+                attribute.set_base_value(*value);
+                attribute_set.insert_attribute(attribute);
+            }
+        }
+
+        let mut skill_book = SkillBook::new(self.skill_slots);
+
+        for skill in self.skills.iter() {
+            let _ = skill_book.learn_and_equip(skill.to_skill(), skill.usage.clone());
+        }
+
+        SkillComponent {
+            skill_book,
+            ..SkillComponent::new(attribute_set)
+        }
+    }
+}
+
+///队伍数据
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TeamData {
+    pub name: String,
+    pub units: Vec<UnitData>,
+}
+
+///战斗数据，用于模拟器和测试
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BattleData {
+    pub teams: Vec<TeamData>,
+    #[serde(default = "default_max_turns")]
+    pub max_turns: u32,
+    ///召唤效果可以使用的模板
+    #[serde(default)]
+    pub summons: Vec<SummonTemplate>,
+}
+
+fn default_max_turns() -> u32 {
+    Battle::DEFAULT_MAX_TURNS
+}
+
+impl BattleData {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    ///使用固定种子创建战斗，注册所有内置的技能效果处理器
+    pub fn create_battle(&self, seed: u64) -> Battle {
+        let mut processors = SkillEffectProcessorContainer::new();
+        processors.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+        processors.register_skill_effect_processor(HealSkillEffectProcessor);
+        processors.register_skill_effect_processor(LifestealSkillEffectProcessor);
+        processors.register_skill_effect_processor(TauntSkillEffectProcessor);
+        processors.register_skill_effect_processor(SummonSkillEffectProcessor);
+        processors.register_reaction_processor(ReflectSkillEffectProcessor::default());
+        processors.register_reaction_processor(ThornsSkillEffectProcessor::default());
+        processors.register_reaction_processor(CounterSkillEffectProcessor);
+
+        let mut battle = Battle::seeded(processors, seed);
+        battle.max_turns = self.max_turns;
+
+        for template in self.summons.iter() {
+            battle.register_summon(template.clone());
+        }
+
+        for (team, team_data) in self.teams.iter().enumerate() {
+            for unit in team_data.units.iter() {
+                let skill_component = unit.to_skill_component(&battle.constraints);
+                let ai = unit.ai.to_battle_ai();
+
+                let id = battle.add_boxed_combatant(&unit.name, team, skill_component, vec![], ai);
+
+                for passive in unit.passives.iter() {
+                    battle.add_passive(id, passive.to_passive_skill());
+                }
+            }
+        }
+
+        battle
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        AiData, AttributeId, AttributeUtils, BattleData, CombatantId, SkillTags, SkillTrigger,
+        TriggerTarget,
+    };
+
+    #[test]
+    fn test_battle_data() {
+        let data = BattleData::from_json(
+            r#"{
+                "teams": [
+                    {
+                        "name": "heroes",
+                        "units": [
+                            {
+                                "name": "knight",
+                                "attributes": { "max_health": 200, "speed": 5 },
+                                "skills": [
+                                    {
+                                        "skill_name": "slash",
+                                        "tags": ["physical", "melee"],
+                                        "cooldown": 2,
+                                        "costs": { "power": 5 },
+                                        "effects": [
+                                            {
+                                                "name": "damage_skill_effect",
+                                                "payload": { "min_damage": 30, "crit_chance": 0.1 },
+                                                "scaling": { "min_damage": { "type": "linear", "base": 30, "per_level": 10 } }
+                                            }
+                                        ]
+                                    }
+                                ],
+                                "passives": [
+                                    {
+                                        "skill_name": "last_stand",
+                                        "effects": [
+                                            { "name": "heal_skill_effect", "payload": { "min_heal": 20 } }
+                                        ],
+                                        "trigger": { "type": "health_below", "ratio": 0.3 }
+                                    }
+                                ],
+                                "ai": { "type": "utility" }
+                            }
+                        ]
+                    },
+                    { "name": "slimes", "units": [] }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(100, data.max_turns);
+        assert!(matches!(data.teams[0].units[0].ai, AiData::Utility { .. }));
+        assert_eq!(
+            data,
+            BattleData::from_json(&data.to_json().unwrap()).unwrap()
+        );
+
+        let mut battle = data.create_battle(1);
+        let knight = &battle.combatants()[0];
+        assert_eq!(200.0, knight.health());
+        assert_eq!(
+            Some(5.0),
+            knight
+                .skill_component
+                .attribute_set
+                .get_current_value("speed")
+        );
+        assert_eq!(
+            Some(30.0),
+            knight.skill(0).unwrap().effcts[0].get_number("min_damage")
+        );
+        assert_eq!(
+            Some(200.0),
+            knight
+                .skill_component
+                .attribute_set
+                .get_current_value(AttributeUtils::MAX_HEALTH)
+        );
+        assert_eq!(
+            SkillTrigger::HealthBelow { ratio: 0.3 },
+            knight.passives[0].trigger
+        );
+        assert_eq!(TriggerTarget::Owner, knight.passives[0].target);
+
+        let slash = knight.skill_component.skill_book.get("slash").unwrap();
+        assert_eq!(2, slash.usage.cooldown);
+        assert_eq!(
+            Some(&5.0),
+            slash
+                .usage
+                .costs
+                .get(&AttributeId::new(AttributeUtils::POWER))
+        );
+        assert!(knight.skill_component.skill_book.is_equipped("slash"));
+        assert!(knight.skill(0).unwrap().has_tag(SkillTags::MELEE));
+
+        assert_eq!(Some(2), battle.upgrade_skill(CombatantId(0), "slash"));
+        assert_eq!(None, battle.upgrade_skill(CombatantId(0), "fireball"));
+
+        let slash = battle.combatants()[0].skill(0).unwrap();
+        assert_eq!(
+            Some(40.0),
+            slash.effcts[0]
+                .at_level(slash.level)
+                .unwrap()
+                .get_number("min_damage")
+        );
+    }
+}
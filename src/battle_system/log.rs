@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{NumberRecord, SkillProperty, SkillUnavailable};
+
+use super::CombatantId;
+
+///单位死亡时记录的状态
+pub const DEAD_STATUS: &str = "dead";
+
+///战斗中发生的事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum CombatEvent {
+    CombatantJoined {
+        id: CombatantId,
+        name: String,
+        team: usize,
+    },
+    RoundStarted,
+    ///召唤物消失
+    CombatantRemoved {
+        id: CombatantId,
+    },
+    SkillCast {
+        caster: CombatantId,
+        target: CombatantId,
+        skill_name: String,
+    },
+    ///开始蓄力或者引导
+    SkillStarted {
+        caster: CombatantId,
+        target: CombatantId,
+        skill_name: String,
+        turns: u32,
+    },
+    ///目标不满足技能的目标规则或者技能不能使用，单位没有行动
+    SkillRejected {
+        caster: CombatantId,
+        target: CombatantId,
+        skill_name: String,
+        ///技能不能使用的原因，例如冷却和消耗不足，目标无效时为 None
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<SkillUnavailable>,
+    },
+    ///蓄力或者引导被打断
+    SkillInterrupted {
+        caster: CombatantId,
+        skill_name: String,
+    },
+    ///随机数生成器的一次调用
+    Roll {
+        record: NumberRecord,
+    },
+    EffectResult {
+        caster: CombatantId,
+        target: CombatantId,
+        skill_effect_name: String,
+        values: BTreeMap<String, SkillProperty>,
+    },
+    AttributeChanged {
+        id: CombatantId,
+        attribute_name: String,
+        previous_value: f32,
+        value: f32,
+    },
+    StatusChanged {
+        id: CombatantId,
+        status: String,
+        active: bool,
+    },
+    BattleEnded {
+        winner: Option<usize>,
+    },
+}
+
+///战斗日志中的一条记录
+///
+///round 为回合数，turn 为回合中行动的序号，回合开始前的事件 turn 为 0
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CombatLogEntry {
+    pub round: u32,
+    pub turn: u32,
+    #[serde(flatten)]
+    pub event: CombatEvent,
+}
+
+///战斗日志，可以导出为 JSON lines 用于问题反馈和数据分析
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CombatLog {
+    entries: Vec<CombatLogEntry>,
+}
+
+impl CombatLog {
+    pub fn push(&mut self, round: u32, turn: u32, event: CombatEvent) {
+        self.entries.push(CombatLogEntry { round, turn, event });
+    }
+
+    pub fn entries(&self) -> &[CombatLogEntry] {
+        &self.entries
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CombatLogEntry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    ///每条记录一行 JSON
+    pub fn to_json_lines(&self) -> Result<String, serde_json::Error> {
+        let mut lines = String::new();
+
+        for entry in self.entries.iter() {
+            lines.push_str(&serde_json::to_string(entry)?);
+            lines.push('\n');
+        }
+
+        Ok(lines)
+    }
+
+    ///读取 to_json_lines 导出的日志，忽略空行
+    pub fn from_json_lines(lines: &str) -> Result<Self, serde_json::Error> {
+        let entries = lines
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { entries })
+    }
+
+    fn effect_number<'a>(
+        &'a self,
+        key: &'a str,
+        filter: impl Fn(CombatantId, CombatantId) -> bool + 'a,
+    ) -> f32 {
+        self.entries
+            .iter()
+            .filter_map(|entry| match &entry.event {
+                CombatEvent::EffectResult {
+                    caster,
+                    target,
+                    values,
+                    ..
+                } if filter(*caster, *target) => values.get(key).and_then(|v| v.as_number()),
+                _ => None,
+            })
+            .sum()
+    }
+
+    ///id 造成的伤害，key 为伤害效果结果中的键
+    pub fn damage_dealt_by(&self, id: CombatantId, key: &str) -> f32 {
+        self.effect_number(key, |caster, _| caster == id)
+    }
+
+    ///id 受到的伤害
+    pub fn damage_taken_by(&self, id: CombatantId, key: &str) -> f32 {
+        self.effect_number(key, |_, target| target == id)
+    }
+
+    ///id 释放的技能
+    pub fn casts_by(&self, id: CombatantId) -> impl Iterator<Item = &CombatLogEntry> {
+        self.entries.iter().filter(move |entry| {
+            matches!(entry.event, CombatEvent::SkillCast { caster, .. } if caster == id)
+        })
+    }
+
+    ///id 死亡时的回合数，没有死亡时返回 None
+    pub fn turns_until_death(&self, id: CombatantId) -> Option<u32> {
+        self.entries.iter().find_map(|entry| match &entry.event {
+            CombatEvent::StatusChanged {
+                id: combatant,
+                status,
+                active: true,
+            } if *combatant == id && status == DEAD_STATUS => Some(entry.round),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        AttributeUtils, Battle, CombatEvent, CombatLog, CombatantId, DamageSkillEffectProcessor,
+        NumberGeneratorContainer, RandomBattleAi, Skill, SkillComponent, SkillEffect,
+        SkillEffectProcessorContainer, SkillTarget,
+    };
+
+    #[test]
+    fn test_combat_log() {
+        let mut processors = SkillEffectProcessorContainer::new();
+        processors.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+
+        let mut battle = Battle::new(processors, NumberGeneratorContainer::seeded(11));
+        battle.enable_log();
+
+        for (name, team, damage) in [("knight", 0, 60.0), ("slime", 1, 10.0)] {
+            let skill = Skill::new(
+                "slash",
+                vec![
+                    SkillEffect::new("damage_skill_effect")
+                        .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, damage)
+                        .with_value(DamageSkillEffectProcessor::CRIT_CHANCE, 0.5),
+                ],
+            )
+            .with_target(SkillTarget::Enemy);
+
+            battle.add_combatant(
+                name,
+                team,
+                SkillComponent::new(AttributeUtils::new_skill_attribute_set()),
+                vec![skill],
+                RandomBattleAi::default(),
+            );
+        }
+
+        let outcome = battle.run().unwrap();
+        let log = battle.take_log().unwrap();
+
+        let knight = CombatantId(0);
+        let slime = CombatantId(1);
+
+        assert!(log.damage_dealt_by(knight, DamageSkillEffectProcessor::DAMAGE) >= 100.0);
+        assert_eq!(
+            log.damage_dealt_by(slime, DamageSkillEffectProcessor::DAMAGE),
+            log.damage_taken_by(knight, DamageSkillEffectProcessor::DAMAGE)
+        );
+        assert_eq!(Some(outcome.turns), log.turns_until_death(slime));
+        assert_eq!(None, log.turns_until_death(knight));
+        assert_eq!(outcome.actions.len(), log.casts_by(knight).count() * 2 - 1);
+        assert!(
+            log.iter()
+                .any(|entry| matches!(entry.event, CombatEvent::Roll { .. }))
+        );
+        assert!(matches!(
+            log.entries().last().map(|entry| &entry.event),
+            Some(CombatEvent::BattleEnded { winner: Some(0) })
+        ));
+
+        let lines = log.to_json_lines().unwrap();
+        assert_eq!(log.len(), lines.lines().count());
+        assert_eq!(log, CombatLog::from_json_lines(&lines).unwrap());
+    }
+}
@@ -0,0 +1,1009 @@
+mod casting;
+mod data;
+mod log;
+mod replay;
+mod simulation;
+mod snapshot;
+mod summon;
+mod threat;
+mod trigger;
+
+pub use casting::*;
+pub use data::*;
+pub use log::*;
+pub use replay::*;
+pub use simulation::*;
+pub use snapshot::*;
+pub use summon::*;
+pub use threat::*;
+pub use trigger::*;
+
+use std::{collections::BTreeMap, fmt};
+
+use bevy::ecs::entity::Entity;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AiContext, AiTarget, AttributeConstraintProcessorContainer, AttributeUtils, BattleAi,
+    CounterSkillEffectProcessor, DamagePipeline, DamageSkillEffectProcessor,
+    HealSkillEffectProcessor, LifestealSkillEffectProcessor, NumberGeneratorContainer,
+    NumberRecorder, ReflectSkillEffectProcessor, SeededNumberGenerator, Skill, SkillBook,
+    SkillComponent, SkillContext, SkillEffectId, SkillEffectProcessorContainer, SkillError,
+    SkillStatus, SkillTag, SkillTarget, SkillUsage, TauntSkillEffectProcessor,
+};
+
+///战斗单位标识符，在一场战斗中唯一
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize,
+)]
+pub struct CombatantId(pub u32);
+
+impl fmt::Display for CombatantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+///参与战斗的单位
+pub struct Combatant {
+    pub id: CombatantId,
+    pub name: String,
+    pub team: usize,
+    ///技能书中装备的技能为单位可以使用的技能
+    pub skill_component: SkillComponent,
+    pub passives: Vec<PassiveSkill>,
+    ///正在蓄力或者引导的技能
+    pub pending: Option<PendingSkill>,
+    pub threat: ThreatTable,
+    ///召唤物的来源，不是召唤物时为 None
+    pub summon: Option<Summon>,
+    ///召唤物在 bevy 中对应的实体，由 sync_summon_entities 创建和删除
+    pub entity: Option<Entity>,
+    ///召唤物消失之后为 true，不再参与战斗
+    pub removed: bool,
+}
+
+impl Combatant {
+    pub fn health(&self) -> f32 {
+        self.skill_component
+            .attribute_set
+            .get_current_value(AttributeUtils::HEALTH)
+            .unwrap_or(0.0)
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.health() > 0.0
+    }
+
+    ///装备的技能，技能的下标为在其中的位置
+    pub fn skills(&self) -> impl Iterator<Item = &Skill> {
+        self.skill_component.skills()
+    }
+
+    pub fn skill(&self, index: usize) -> Option<&Skill> {
+        self.skills().nth(index)
+    }
+
+    pub fn find_skill(&self, skill_name: &str) -> Option<&Skill> {
+        self.skills().find(|skill| skill.skill_name == skill_name)
+    }
+}
+
+///一次行动的结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BattleAction {
+    pub turn: u32,
+    pub caster: CombatantId,
+    pub target: CombatantId,
+    pub skill_name: String,
+    pub damage: f32,
+    pub heal: f32,
+    pub hit: bool,
+    pub crit: bool,
+    ///被动技能或者反击触发的行动
+    #[serde(default)]
+    pub triggered: bool,
+    ///目标的反射和荆棘对施法者造成的伤害
+    #[serde(default)]
+    pub caster_damage: f32,
+    ///吸血对施法者的治疗
+    #[serde(default)]
+    pub caster_heal: f32,
+    ///目标是否反击
+    #[serde(default)]
+    pub counter: bool,
+}
+
+///战斗的结果，winner 为 None 时表示平局
+#[derive(Debug, Clone, PartialEq)]
+pub struct BattleOutcome {
+    pub winner: Option<usize>,
+    pub turns: u32,
+    pub actions: Vec<BattleAction>,
+}
+
+///一次生命值的变化，source 为造成变化的单位
+struct HealthChange {
+    index: usize,
+    source: usize,
+    previous_health: f32,
+    health: f32,
+}
+
+///一次技能执行的结果和所有生命值的变化
+struct Cast {
+    action: BattleAction,
+    tags: Vec<SkillTag>,
+    ///嘲讽效果增加的仇恨和嘲讽的回合数
+    threat: f32,
+    taunt_turns: u32,
+    changes: Vec<HealthChange>,
+}
+
+///回合制战斗，不依赖 bevy 的 App，可以在模拟器和测试中直接运行
+///
+///每回合所有存活的单位按照加入的顺序行动，召唤物在召唤者之后行动，只剩一个队伍存活或者达到最大回合数时结束
+pub struct Battle {
+    combatants: Vec<Combatant>,
+    controllers: Vec<Box<dyn BattleAi>>,
+    ///行动顺序，单位的下标
+    order: Vec<usize>,
+    summon_templates: BTreeMap<String, SummonTemplate>,
+    pub processors: SkillEffectProcessorContainer,
+    pub constraints: AttributeConstraintProcessorContainer,
+    pub generators: NumberGeneratorContainer,
+    ///AI 决策使用的随机数生成器，与技能使用的生成器分开，回放时不需要重新运行 AI
+    pub ai_generators: NumberGeneratorContainer,
+    pub max_turns: u32,
+    turn: u32,
+    action_turn: u32,
+    seed: Option<u64>,
+    log: Option<CombatLog>,
+    replay: Option<BattleReplay>,
+}
+
+impl Battle {
+    pub const DEFAULT_MAX_TURNS: u32 = 100;
+
+    ///AI 使用的生成器由 generators 的状态得到种子，generators 可以重现时 AI 的选择也可以重现
+    pub fn new(
+        processors: SkillEffectProcessorContainer,
+        generators: NumberGeneratorContainer,
+    ) -> Self {
+        let ai_generators = generators
+            .derive_seed()
+            .map(NumberGeneratorContainer::seeded)
+            .unwrap_or_default();
+
+        Self {
+            combatants: vec![],
+            controllers: vec![],
+            order: vec![],
+            summon_templates: BTreeMap::new(),
+            processors,
+            constraints: AttributeConstraintProcessorContainer::new(),
+            generators,
+            ai_generators,
+            max_turns: Self::DEFAULT_MAX_TURNS,
+            turn: 0,
+            action_turn: 0,
+            seed: None,
+            log: None,
+            replay: None,
+        }
+    }
+
+    ///使用固定种子创建战斗，技能和 AI 的随机数都由 seed 生成
+    pub fn seeded(processors: SkillEffectProcessorContainer, seed: u64) -> Self {
+        let mut battle = Self::new(processors, NumberGeneratorContainer::seeded(seed));
+        battle.ai_generators =
+            NumberGeneratorContainer::seeded(SeededNumberGenerator::new(seed).next_u64());
+        battle.seed = Some(seed);
+
+        battle
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    ///开始记录战斗日志，包括所有的随机数
+    pub fn enable_log(&mut self) {
+        let mut log = CombatLog::default();
+
+        for combatant in self.combatants.iter() {
+            log.push(
+                self.turn,
+                0,
+                CombatEvent::CombatantJoined {
+                    id: combatant.id,
+                    name: combatant.name.clone(),
+                    team: combatant.team,
+                },
+            );
+        }
+
+        self.log = Some(log);
+        self.generators.set_recorder(NumberRecorder::default());
+    }
+
+    pub fn log(&self) -> Option<&CombatLog> {
+        self.log.as_ref()
+    }
+
+    ///取出战斗日志并停止记录
+    pub fn take_log(&mut self) -> Option<CombatLog> {
+        self.generators.clear_recorder();
+        self.log.take()
+    }
+
+    fn push_event(&mut self, event: CombatEvent) {
+        if let Some(log) = self.log.as_mut() {
+            log.push(self.turn, self.action_turn, event);
+        }
+    }
+
+    ///把随机数生成器的记录写入日志
+    fn push_rolls(&mut self) {
+        let Some(records) = self.generators.recorder().map(NumberRecorder::take) else {
+            return;
+        };
+
+        for record in records {
+            self.push_event(CombatEvent::Roll { record });
+        }
+    }
+
+    ///加入单位，单位的阵营为 team
+    ///
+    ///skills 中没有记录在技能书中的技能使用默认的使用限制学会并装备，装备栏已满时无法使用
+    pub fn add_combatant<T: BattleAi>(
+        &mut self,
+        name: &str,
+        team: usize,
+        skill_component: SkillComponent,
+        skills: Vec<Skill>,
+        ai: T,
+    ) -> CombatantId {
+        self.add_boxed_combatant(name, team, skill_component, skills, Box::new(ai))
+    }
+
+    pub fn add_boxed_combatant(
+        &mut self,
+        name: &str,
+        team: usize,
+        mut skill_component: SkillComponent,
+        skills: Vec<Skill>,
+        ai: Box<dyn BattleAi>,
+    ) -> CombatantId {
+        let id = CombatantId(self.combatants.len() as u32);
+        skill_component.faction = team;
+        skill_component.owner = Some(id.0);
+
+        for skill in skills {
+            let _ = skill_component
+                .skill_book
+                .learn_and_equip(skill, SkillUsage::default());
+        }
+
+        self.combatants.push(Combatant {
+            id,
+            name: name.to_string(),
+            team,
+            skill_component,
+            passives: vec![],
+            pending: None,
+            threat: ThreatTable::default(),
+            summon: None,
+            entity: None,
+            removed: false,
+        });
+        self.controllers.push(ai);
+        self.order.push(id.0 as usize);
+
+        self.push_event(CombatEvent::CombatantJoined {
+            id,
+            name: name.to_string(),
+            team,
+        });
+
+        id
+    }
+
+    pub fn combatants(&self) -> &[Combatant] {
+        &self.combatants
+    }
+
+    pub fn get(&self, id: CombatantId) -> Option<&Combatant> {
+        self.combatants.get(id.0 as usize)
+    }
+
+    ///caster 是否可以对 target 使用技能，消失的召唤物不能被选择
+    pub fn is_valid_target(&self, caster: CombatantId, skill: &Skill, target: CombatantId) -> bool {
+        match (self.get(caster), self.get(target)) {
+            (Some(caster), Some(target)) => {
+                !target.removed
+                    && skill
+                        .target
+                        .allows(&caster.skill_component, &target.skill_component)
+            }
+            _ => false,
+        }
+    }
+
+    ///按照技能的目标规则自动选择目标，任意目标的技能优先选择敌方，其它情况选择第一个满足规则的单位
+    pub fn auto_target(&self, caster: CombatantId, skill_name: &str) -> Option<CombatantId> {
+        let combatant = self.get(caster)?;
+        let skill = combatant.find_skill(skill_name)?;
+
+        let targets = self
+            .combatants
+            .iter()
+            .filter(|target| self.is_valid_target(caster, skill, target.id))
+            .collect::<Vec<_>>();
+
+        match skill.target {
+            SkillTarget::Any => targets
+                .iter()
+                .find(|target| target.team != combatant.team)
+                .or(targets.first()),
+            _ => targets.first(),
+        }
+        .map(|target| target.id)
+    }
+
+    ///单位的行动顺序，包括已经死亡的单位，不包括消失的召唤物
+    pub fn turn_order(&self) -> impl Iterator<Item = CombatantId> {
+        self.order.iter().map(|index| self.combatants[*index].id)
+    }
+
+    ///升级单位名为 skill_name 的技能，返回新的等级，单位或者技能不存在时返回 None
+    pub fn upgrade_skill(&mut self, id: CombatantId, skill_name: &str) -> Option<u32> {
+        self.combatants
+            .get_mut(id.0 as usize)?
+            .skill_component
+            .skill_book
+            .upgrade(skill_name)
+    }
+
+    ///为单位添加状态，单位不存在时返回 false
+    ///
+    ///眩晕和禁用了正在蓄力或者引导的技能的状态会打断这个技能，不能被打断的技能不受影响
+    pub fn add_status(&mut self, id: CombatantId, status: SkillStatus) -> bool {
+        let Some(combatant) = self.combatants.get_mut(id.0 as usize) else {
+            return false;
+        };
+
+        let interrupt = status.stun
+            || combatant.pending.as_ref().is_some_and(|pending| {
+                combatant
+                    .find_skill(&pending.skill_name)
+                    .is_some_and(|skill| status.blocked_tag(skill).is_some())
+            });
+
+        let name = status.name.clone();
+        combatant.skill_component.add_status(status);
+        self.push_event(CombatEvent::StatusChanged {
+            id,
+            status: name,
+            active: true,
+        });
+
+        if interrupt {
+            self.interrupt(id);
+        }
+
+        true
+    }
+
+    pub fn remove_status(&mut self, id: CombatantId, name: &str) -> Option<SkillStatus> {
+        let status = self
+            .combatants
+            .get_mut(id.0 as usize)?
+            .skill_component
+            .remove_status(name)?;
+
+        self.push_event(CombatEvent::StatusChanged {
+            id,
+            status: status.name.clone(),
+            active: false,
+        });
+
+        Some(status)
+    }
+
+    ///单位的技能书，用于在战斗中装备或者卸下技能
+    pub fn skill_book_mut(&mut self, id: CombatantId) -> Option<&mut SkillBook> {
+        self.combatants
+            .get_mut(id.0 as usize)
+            .map(|combatant| &mut combatant.skill_component.skill_book)
+    }
+
+    ///已经开始的回合数
+    pub fn turn(&self) -> u32 {
+        self.turn
+    }
+
+    ///还有存活单位的队伍
+    pub fn alive_teams(&self) -> Vec<usize> {
+        let mut teams = self
+            .combatants
+            .iter()
+            .filter(|combatant| combatant.is_alive())
+            .map(|combatant| combatant.team)
+            .collect::<Vec<_>>();
+
+        teams.sort_unstable();
+        teams.dedup();
+
+        teams
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        match self.alive_teams().as_slice() {
+            [team] => Some(*team),
+            _ => None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.alive_teams().len() <= 1 || self.turn >= self.max_turns
+    }
+
+    ///进行一个回合，返回这个回合中所有的行动
+    ///
+    ///被动技能触发的行动跟在触发它的行动之后
+    pub fn run_turn(&mut self) -> Result<Vec<BattleAction>, SkillError> {
+        let mut actions = self.begin_round()?;
+
+        for index in self.order.clone() {
+            if self.alive_teams().len() <= 1 {
+                break;
+            }
+
+            if !self.combatants[index].is_alive() {
+                continue;
+            }
+
+            if let Some(action_actions) = self.act(index)? {
+                actions.extend(action_actions);
+            }
+        }
+
+        Ok(actions)
+    }
+
+    ///开始新的回合，返回回合开始时触发的行动
+    fn begin_round(&mut self) -> Result<Vec<BattleAction>, SkillError> {
+        self.turn += 1;
+        self.action_turn = 0;
+        self.push_event(CombatEvent::RoundStarted);
+
+        for index in 0..self.combatants.len() {
+            let skill_component = &mut self.combatants[index].skill_component;
+            skill_component.skill_book.tick();
+
+            for status in skill_component.tick_statuses() {
+                self.push_event(CombatEvent::StatusChanged {
+                    id: self.combatants[index].id,
+                    status: status.name,
+                    active: false,
+                });
+            }
+        }
+
+        self.tick_summons();
+
+        let mut actions = vec![];
+        self.trigger_reactions(self.turn_start_firings(), &mut actions)?;
+
+        Ok(actions)
+    }
+
+    ///进行战斗直到结束
+    pub fn run(&mut self) -> Result<BattleOutcome, SkillError> {
+        let mut actions = vec![];
+
+        while !self.is_finished() {
+            actions.extend(self.run_turn()?);
+        }
+
+        let winner = self.winner();
+        self.push_event(CombatEvent::BattleEnded { winner });
+
+        Ok(BattleOutcome {
+            winner,
+            turns: self.turn,
+            actions,
+        })
+    }
+
+    fn act(&mut self, index: usize) -> Result<Option<Vec<BattleAction>>, SkillError> {
+        if self.combatants[index].pending.is_some() {
+            return self.continue_skill(index);
+        }
+
+        let caster = &self.combatants[index];
+        let taunt = self.taunted_by(index);
+
+        let targets = self
+            .combatants
+            .iter()
+            .map(|combatant| AiTarget {
+                skill_component: &combatant.skill_component,
+                ally: combatant.team == caster.team,
+                pending: combatant.pending.as_ref(),
+                threat: caster.threat.get(combatant.id),
+                removed: combatant.removed,
+            })
+            .collect::<Vec<_>>();
+
+        let context = AiContext {
+            caster: &caster.skill_component,
+            targets: &targets,
+            processors: &self.processors,
+            taunt,
+        };
+
+        let action = self.controllers[index].choose_action(&context, &mut self.ai_generators);
+
+        match action {
+            //被嘲讽时只能攻击嘲讽的来源
+            Some(action) => {
+                let target = match taunt {
+                    Some(taunt) if !targets[action.target].ally => taunt,
+                    _ => action.target,
+                };
+
+                self.start_skill(index, action.skill, target)
+            }
+            None => Ok(None),
+        }
+    }
+
+    ///执行 caster_index 的第 skill_index 个技能和它触发的被动技能，下标无效时返回 None
+    ///
+    ///第一个行动为执行的技能，之后为触发的行动
+    fn perform(
+        &mut self,
+        caster_index: usize,
+        skill_index: usize,
+        target_index: usize,
+    ) -> Result<Option<Vec<BattleAction>>, SkillError> {
+        if target_index >= self.combatants.len() {
+            return Ok(None);
+        }
+
+        let Some(skill) = self
+            .combatants
+            .get(caster_index)
+            .and_then(|caster| caster.skill(skill_index))
+            .cloned()
+        else {
+            return Ok(None);
+        };
+
+        let cast = self.cast(caster_index, &skill, target_index, false)?;
+        let mut actions = vec![cast.action.clone()];
+        self.trigger_reactions(self.action_firings(&cast), &mut actions)?;
+
+        if let Some(replay) = self.replay.as_mut() {
+            replay.push_step(&actions[0], skill_index, &self.combatants);
+        }
+
+        Ok(Some(actions))
+    }
+
+    ///执行技能并修改目标的生命值，记录所有事件
+    fn cast(
+        &mut self,
+        caster_index: usize,
+        skill: &Skill,
+        target_index: usize,
+        triggered: bool,
+    ) -> Result<Cast, SkillError> {
+        let caster = &self.combatants[caster_index];
+        let target = &self.combatants[target_index];
+
+        self.action_turn += 1;
+
+        let skill_context = self.processors.execute(
+            &caster.skill_component,
+            &target.skill_component,
+            skill,
+            &mut self.generators,
+        )?;
+
+        let battle_action = BattleAction {
+            turn: self.turn,
+            caster: caster.id,
+            target: target.id,
+            skill_name: skill.skill_name.clone(),
+            damage: skill_context.sum_number(DamageSkillEffectProcessor::DAMAGE),
+            heal: skill_context.sum_number(HealSkillEffectProcessor::HEAL),
+            hit: skill_context.any_bool(DamageSkillEffectProcessor::HIT),
+            crit: skill_context.any_bool(DamageSkillEffectProcessor::CRIT),
+            triggered,
+            caster_damage: skill_context.sum_number(ReflectSkillEffectProcessor::CASTER_DAMAGE),
+            caster_heal: skill_context.sum_number(LifestealSkillEffectProcessor::CASTER_HEAL),
+            counter: skill_context.any_bool(CounterSkillEffectProcessor::COUNTER),
+        };
+
+        let shield_absorbed = skill_context.sum_number(DamagePipeline::SHIELD_ABSORBED);
+        let caster_shield_absorbed =
+            skill_context.sum_number(ReflectSkillEffectProcessor::CASTER_SHIELD_ABSORBED);
+
+        let effect_events = if self.log.is_some() {
+            Self::effect_events(
+                &battle_action,
+                skill,
+                self.processors.reactions(),
+                &skill_context,
+            )
+        } else {
+            vec![]
+        };
+
+        self.push_event(CombatEvent::SkillCast {
+            caster: battle_action.caster,
+            target: battle_action.target,
+            skill_name: battle_action.skill_name.clone(),
+        });
+        self.push_rolls();
+
+        for event in effect_events {
+            self.push_event(event);
+        }
+
+        if shield_absorbed > 0.0 {
+            self.consume_shield(target_index, shield_absorbed);
+        }
+
+        if caster_shield_absorbed > 0.0 {
+            self.consume_shield(caster_index, caster_shield_absorbed);
+        }
+
+        let mut changes = vec![];
+        changes.extend(self.change_health(
+            target_index,
+            caster_index,
+            battle_action.damage,
+            battle_action.heal,
+        ));
+
+        if battle_action.caster_damage != 0.0 || battle_action.caster_heal != 0.0 {
+            changes.extend(self.change_health(
+                caster_index,
+                target_index,
+                battle_action.caster_damage,
+                battle_action.caster_heal,
+            ));
+        }
+
+        let cast = Cast {
+            action: battle_action,
+            tags: skill.tags.clone(),
+            threat: skill_context.sum_number(TauntSkillEffectProcessor::THREAT),
+            taunt_turns: skill_context.sum_number(TauntSkillEffectProcessor::TAUNT_TURNS) as u32,
+            changes,
+        };
+        self.record_threat(&cast);
+        self.summon_from_context(cast.action.caster, &skill_context);
+
+        Ok(cast)
+    }
+
+    ///扣除护盾吸收的伤害并记录事件
+    fn consume_shield(&mut self, index: usize, absorbed: f32) {
+        let id = self.combatants[index].id;
+        let attribute_set = &mut self.combatants[index].skill_component.attribute_set;
+
+        let Some(previous_value) = attribute_set.get_current_value(AttributeUtils::SHIELD) else {
+            return;
+        };
+
+        let shield = (previous_value - absorbed).max(0.0);
+        let value = attribute_set
+            .set_current_value(AttributeUtils::SHIELD, shield, &self.constraints)
+            .unwrap_or(shield);
+
+        self.push_event(CombatEvent::AttributeChanged {
+            id,
+            attribute_name: AttributeUtils::SHIELD.to_string(),
+            previous_value,
+            value,
+        });
+    }
+
+    ///修改单位的生命值并记录事件，单位没有生命值时返回 None
+    fn change_health(
+        &mut self,
+        index: usize,
+        source: usize,
+        damage: f32,
+        heal: f32,
+    ) -> Option<HealthChange> {
+        let id = self.combatants[index].id;
+        let attribute_set = &mut self.combatants[index].skill_component.attribute_set;
+        let previous_value = attribute_set.get_current_value(AttributeUtils::HEALTH)?;
+
+        let health = (previous_value - damage + heal).max(0.0);
+        let value = attribute_set
+            .set_current_value(AttributeUtils::HEALTH, health, &self.constraints)
+            .unwrap_or(health);
+
+        if value != previous_value {
+            self.push_event(CombatEvent::AttributeChanged {
+                id,
+                attribute_name: AttributeUtils::HEALTH.to_string(),
+                previous_value,
+                value,
+            });
+        }
+
+        if previous_value > 0.0 && value <= 0.0 {
+            self.push_event(CombatEvent::StatusChanged {
+                id,
+                status: DEAD_STATUS.to_string(),
+                active: true,
+            });
+            self.dismiss_summons(index);
+        }
+
+        if value < previous_value {
+            self.record_damage_taken(index, previous_value - value);
+        }
+
+        Some(HealthChange {
+            index,
+            source,
+            previous_health: previous_value,
+            health: value,
+        })
+    }
+
+    ///按照技能中效果的顺序生成效果结果事件
+    ///技能中的效果和目标的反应效果的结果
+    fn effect_events(
+        action: &BattleAction,
+        skill: &Skill,
+        reactions: &[SkillEffectId],
+        skill_context: &SkillContext,
+    ) -> Vec<CombatEvent> {
+        let reactions = reactions.iter().filter(|reaction| {
+            !skill
+                .effcts
+                .iter()
+                .any(|skill_effect| skill_effect.skill_effect_name == **reaction)
+        });
+
+        skill
+            .effcts
+            .iter()
+            .map(|skill_effect| &skill_effect.skill_effect_name)
+            .chain(reactions)
+            .filter_map(|skill_effect_name| {
+                let result = skill_context.get_result(*skill_effect_name)?;
+
+                Some(CombatEvent::EffectResult {
+                    caster: action.caster,
+                    target: action.target,
+                    skill_effect_name: skill_effect_name.to_string(),
+                    values: result
+                        .iter()
+                        .map(|(key, value)| (key.to_string(), value.clone()))
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        AttributeUtils, Battle, CombatEvent, CombatantId, DamageSkillEffectProcessor,
+        NumberGeneratorContainer, NumberGeneratorState, NumberRecord, PRD_NUMBER_GENERATOR,
+        RandomBattleAi, Skill, SkillComponent, SkillEffect, SkillEffectProcessorContainer,
+        SkillError, SkillTarget,
+    };
+
+    fn new_battle(seed: u64) -> Battle {
+        let mut processors = SkillEffectProcessorContainer::new();
+        processors.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+
+        let mut battle = Battle::new(processors, NumberGeneratorContainer::seeded(seed));
+
+        for (name, team, damage) in [("knight", 0, 40.0), ("slime", 1, 20.0)] {
+            let skill = Skill::new(
+                "slash",
+                vec![
+                    SkillEffect::new("damage_skill_effect")
+                        .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, damage)
+                        .with_value(DamageSkillEffectProcessor::MAX_DAMAGE, damage + 10.0),
+                ],
+            )
+            .with_target(SkillTarget::Enemy);
+            let jab = Skill::new(
+                "jab",
+                vec![
+                    SkillEffect::new("damage_skill_effect")
+                        .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, damage / 2.0),
+                ],
+            )
+            .with_target(SkillTarget::Enemy);
+
+            battle.add_combatant(
+                name,
+                team,
+                SkillComponent::new(AttributeUtils::new_skill_attribute_set()),
+                vec![skill, jab],
+                RandomBattleAi::default(),
+            );
+        }
+
+        battle
+    }
+
+    #[test]
+    fn test_battle() {
+        let mut battle = new_battle(7);
+        let outcome = battle.run().unwrap();
+
+        assert_eq!(Some(0), outcome.winner);
+        assert!(battle.is_finished());
+        assert!(!battle.combatants()[1].is_alive());
+        assert_eq!(outcome.turns, battle.turn());
+
+        //AI 的随机数由技能的生成器得到，相同的种子得到相同的选择
+        let replayed = new_battle(7).run().unwrap();
+        assert_eq!(outcome, replayed);
+        assert!(
+            outcome
+                .actions
+                .iter()
+                .any(|action| action.skill_name == "jab")
+        );
+    }
+
+    #[test]
+    fn test_skills_from_skill_book() {
+        let mut battle = new_battle(7);
+        let knight = CombatantId(0);
+
+        //加入战斗的技能记录在技能书中，同样受到冷却的限制，卸下的技能不能使用
+        let skill_book = &mut battle.combatants[0].skill_component.skill_book;
+        skill_book.get_mut("slash").unwrap().usage.cooldown = 3;
+        assert!(skill_book.unequip("jab"));
+        assert_eq!(
+            vec!["slash"],
+            battle.combatants[0]
+                .skills()
+                .map(|skill| skill.skill_name.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        let actions = battle.run_turn().unwrap();
+        assert!(actions.iter().any(|action| action.caster == knight));
+
+        let actions = battle.run_turn().unwrap();
+        assert!(actions.iter().all(|action| action.caster != knight));
+    }
+
+    #[test]
+    fn test_crit_uses_prd() {
+        let mut battle = new_battle(3);
+        battle.combatants[0]
+            .skill_component
+            .skill_book
+            .get_mut("slash")
+            .unwrap()
+            .skill
+            .effcts[0]
+            .payload
+            .insert(DamageSkillEffectProcessor::CRIT_CHANCE.into(), 0.1.into());
+        battle.enable_log();
+        battle.run_turn().unwrap();
+
+        //暴击使用伪随机生成器，并且按照施法者和效果记录状态
+        let chance = battle
+            .log()
+            .unwrap()
+            .iter()
+            .find_map(|entry| match &entry.event {
+                CombatEvent::Roll {
+                    record: NumberRecord::Roll { chance, .. },
+                } if chance.name == PRD_NUMBER_GENERATOR => Some(chance.clone()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(Some("0/damage_skill_effect/crit"), chance.key.as_deref());
+
+        let state = battle.generators.save_state().unwrap();
+        let Some(NumberGeneratorState::Prd { failures, .. }) =
+            state.generators.get(PRD_NUMBER_GENERATOR)
+        else {
+            panic!("prd generator is not saved");
+        };
+        assert!(
+            failures
+                .keys()
+                .all(|key| key == "0/damage_skill_effect/crit")
+        );
+    }
+
+    #[test]
+    fn test_target_rules() {
+        let mut battle = new_battle(1);
+        battle.add_combatant(
+            "squire",
+            0,
+            SkillComponent::new(AttributeUtils::new_skill_attribute_set()),
+            vec![],
+            RandomBattleAi::default(),
+        );
+
+        let knight = CombatantId(0);
+        let slime = CombatantId(1);
+        let squire = CombatantId(2);
+        battle.enable_log();
+
+        let with_target = |battle: &mut Battle, target: SkillTarget| {
+            let learned = battle.combatants[0]
+                .skill_component
+                .skill_book
+                .get_mut("slash")
+                .unwrap();
+            learned.skill.target = target;
+            learned.skill.clone()
+        };
+
+        //敌方技能不能选择友方
+        let skill = with_target(&mut battle, SkillTarget::Enemy);
+        assert!(battle.is_valid_target(knight, &skill, slime));
+        assert!(!battle.is_valid_target(knight, &skill, squire));
+        assert_eq!(Some(vec![]), battle.start_skill(0, 0, 2).unwrap());
+        assert_eq!(100.0, battle.combatants[2].health());
+        assert!(matches!(
+            battle.log().unwrap().entries().last().map(|entry| &entry.event),
+            Some(CombatEvent::SkillRejected { target, .. }) if *target == squire
+        ));
+        assert!(matches!(
+            battle.processors.execute(
+                &battle.combatants[0].skill_component,
+                &battle.combatants[2].skill_component,
+                &skill,
+                &mut NumberGeneratorContainer::empty(),
+            ),
+            Err(SkillError::InvalidTarget { .. })
+        ));
+
+        //只能选择自己
+        let skill = with_target(&mut battle, SkillTarget::Caster);
+        assert!(battle.is_valid_target(knight, &skill, knight));
+        assert!(!battle.is_valid_target(knight, &skill, squire));
+        assert_eq!(Some(knight), battle.auto_target(knight, "slash"));
+
+        //预览中的施法者和目标为同一个单位
+        let knight_component = &battle.combatants[0].skill_component;
+        assert!(
+            battle
+                .processors
+                .preview(knight_component, &knight_component.clone(), &skill)
+                .is_ok()
+        );
+
+        //任意目标优先选择敌方
+        with_target(&mut battle, SkillTarget::Any);
+        assert_eq!(Some(slime), battle.auto_target(knight, "slash"));
+
+        //死亡的友方
+        let skill = with_target(&mut battle, SkillTarget::DeadAlly);
+        assert_eq!(None, battle.auto_target(knight, "slash"));
+        battle.change_health(2, 1, 1000.0, 0.0);
+        assert!(battle.is_valid_target(knight, &skill, squire));
+        assert!(!battle.is_valid_target(knight, &skill, slime));
+        assert_eq!(Some(squire), battle.auto_target(knight, "slash"));
+        assert!(battle.start_skill(0, 0, 2).unwrap().is_some());
+    }
+}
@@ -0,0 +1,358 @@
+use std::{collections::BTreeMap, error::Error, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AttributeSet, PatternBattleAi, SkillBook, SkillComponent, SkillEffectProcessorContainer,
+    SkillError,
+};
+
+use super::{Battle, BattleAction, Combatant, CombatantId, PassiveSkill, SummonTemplate};
+
+///回放中单位的初始状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayCombatant {
+    pub name: String,
+    pub team: usize,
+    pub attribute_set: AttributeSet,
+    pub skill_book: SkillBook,
+    #[serde(default)]
+    pub passives: Vec<PassiveSkill>,
+}
+
+///回放中的一次行动，values 为行动之后每个单位所有属性的当前值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayStep {
+    pub round: u32,
+    pub caster: CombatantId,
+    pub skill: usize,
+    pub target: CombatantId,
+    pub values: Vec<BTreeMap<String, f32>>,
+}
+
+///战斗回放，包括初始状态、随机数种子和所有的行动
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BattleReplay {
+    pub seed: u64,
+    pub max_turns: u32,
+    pub combatants: Vec<ReplayCombatant>,
+    #[serde(default)]
+    pub summons: Vec<SummonTemplate>,
+    pub steps: Vec<ReplayStep>,
+}
+
+///回放结果与记录不一致的位置，expected 或者 actual 为 None 表示属性不存在
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayDivergence {
+    pub step: usize,
+    pub round: u32,
+    pub combatant: CombatantId,
+    pub attribute_name: String,
+    pub expected: Option<f32>,
+    pub actual: Option<f32>,
+}
+
+///回放错误
+#[derive(Debug)]
+pub enum ReplayError {
+    ///战斗没有使用固定种子
+    NotSeeded,
+    ///战斗已经开始，无法记录初始状态
+    AlreadyStarted,
+    ///记录的行动无法执行，例如技能或者目标的下标无效
+    InvalidStep(usize),
+    Skill(SkillError),
+    Divergence(ReplayDivergence),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::NotSeeded => write!(f, "battle is not seeded"),
+            ReplayError::AlreadyStarted => write!(f, "battle has already started"),
+            ReplayError::InvalidStep(step) => write!(f, "step {step} can not be performed"),
+            ReplayError::Skill(error) => write!(f, "skill failed: {error:?}"),
+            ReplayError::Divergence(divergence) => write!(
+                f,
+                "step {} (round {}) diverged: {} of combatant {} expected {:?}, got {:?}",
+                divergence.step,
+                divergence.round,
+                divergence.attribute_name,
+                divergence.combatant,
+                divergence.expected,
+                divergence.actual
+            ),
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+impl From<SkillError> for ReplayError {
+    fn from(value: SkillError) -> Self {
+        ReplayError::Skill(value)
+    }
+}
+
+fn attribute_values(combatants: &[Combatant]) -> Vec<BTreeMap<String, f32>> {
+    combatants
+        .iter()
+        .map(|combatant| {
+            combatant
+                .skill_component
+                .attribute_set
+                .iter()
+                .map(|attribute| {
+                    (
+                        attribute.attribute_name.to_string(),
+                        attribute.get_current_value(),
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn find_divergence(
+    step: usize,
+    replay_step: &ReplayStep,
+    actual: &[BTreeMap<String, f32>],
+) -> Option<ReplayDivergence> {
+    let divergence = |combatant: usize, name: &str, expected, actual| ReplayDivergence {
+        step,
+        round: replay_step.round,
+        combatant: CombatantId(combatant as u32),
+        attribute_name: name.to_string(),
+        expected,
+        actual,
+    };
+
+    for (combatant, (expected, actual)) in replay_step.values.iter().zip(actual).enumerate() {
+        for (name, value) in expected.iter() {
+            if actual.get(name) != Some(value) {
+                return Some(divergence(
+                    combatant,
+                    name,
+                    Some(*value),
+                    actual.get(name).copied(),
+                ));
+            }
+        }
+
+        for (name, value) in actual.iter() {
+            if !expected.contains_key(name) {
+                return Some(divergence(combatant, name, None, Some(*value)));
+            }
+        }
+    }
+
+    None
+}
+
+impl BattleReplay {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub(super) fn push_step(
+        &mut self,
+        action: &BattleAction,
+        skill: usize,
+        combatants: &[Combatant],
+    ) {
+        self.steps.push(ReplayStep {
+            round: action.turn,
+            caster: action.caster,
+            skill,
+            target: action.target,
+            values: attribute_values(combatants),
+        });
+    }
+
+    ///重新执行所有的行动，每一步之后比较所有属性的当前值
+    ///
+    ///processors 需要与记录时注册的处理器一致，返回回放结束时的战斗
+    pub fn play(&self, processors: SkillEffectProcessorContainer) -> Result<Battle, ReplayError> {
+        let mut battle = Battle::seeded(processors, self.seed);
+        battle.max_turns = self.max_turns;
+
+        for template in self.summons.iter() {
+            battle.register_summon(template.clone());
+        }
+
+        for combatant in self.combatants.iter() {
+            let id = battle.add_combatant(
+                &combatant.name,
+                combatant.team,
+                SkillComponent {
+                    skill_book: combatant.skill_book.clone(),
+                    ..SkillComponent::new(combatant.attribute_set.clone())
+                },
+                vec![],
+                PatternBattleAi::default(),
+            );
+
+            for passive in combatant.passives.iter() {
+                battle.add_passive(id, passive.clone());
+            }
+        }
+
+        for (index, step) in self.steps.iter().enumerate() {
+            while battle.turn < step.round {
+                battle.begin_round()?;
+            }
+
+            battle
+                .perform(step.caster.0 as usize, step.skill, step.target.0 as usize)?
+                .ok_or(ReplayError::InvalidStep(index))?;
+
+            let values = attribute_values(&battle.combatants);
+
+            if let Some(divergence) = find_divergence(index, step, &values) {
+                return Err(ReplayError::Divergence(divergence));
+            }
+        }
+
+        Ok(battle)
+    }
+}
+
+impl Battle {
+    ///开始记录回放，只能在第一个回合开始前记录使用固定种子的战斗
+    ///
+    ///需要在加入所有单位之后调用，之后加入的单位不会被记录
+    pub fn enable_replay(&mut self) -> Result<(), ReplayError> {
+        let seed = self.seed.ok_or(ReplayError::NotSeeded)?;
+
+        if self.turn > 0 {
+            return Err(ReplayError::AlreadyStarted);
+        }
+
+        self.replay = Some(BattleReplay {
+            seed,
+            max_turns: self.max_turns,
+            combatants: self
+                .combatants
+                .iter()
+                .map(|combatant| ReplayCombatant {
+                    name: combatant.name.clone(),
+                    team: combatant.team,
+                    attribute_set: combatant.skill_component.attribute_set.clone(),
+                    skill_book: combatant.skill_component.skill_book.clone(),
+                    passives: combatant.passives.clone(),
+                })
+                .collect(),
+            summons: self.summon_templates().cloned().collect(),
+            steps: vec![],
+        });
+
+        Ok(())
+    }
+
+    pub fn replay(&self) -> Option<&BattleReplay> {
+        self.replay.as_ref()
+    }
+
+    ///取出回放并停止记录
+    pub fn take_replay(&mut self) -> Option<BattleReplay> {
+        self.replay.take()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        Battle, BattleData, BattleReplay, DamageSkillEffectProcessor, HealSkillEffectProcessor,
+        ReplayError, SkillEffectProcessorContainer,
+    };
+
+    fn new_processors() -> SkillEffectProcessorContainer {
+        let mut processors = SkillEffectProcessorContainer::new();
+        processors.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+        processors.register_skill_effect_processor(HealSkillEffectProcessor);
+
+        processors
+    }
+
+    #[test]
+    fn test_battle_replay() {
+        let data = BattleData::from_json(
+            r#"{
+                "teams": [
+                    {
+                        "name": "heroes",
+                        "units": [
+                            {
+                                "name": "knight",
+                                "skills": [
+                                    {
+                                        "skill_name": "slash",
+                                        "effects": [
+                                            { "name": "damage_skill_effect", "payload": { "min_damage": 20, "max_damage": 40, "hit_chance": 0.8, "crit_chance": 0.3 } }
+                                        ]
+                                    },
+                                    {
+                                        "skill_name": "mend",
+                                        "effects": [
+                                            { "name": "heal_skill_effect", "payload": { "min_heal": 10, "max_heal": 30 } }
+                                        ]
+                                    }
+                                ],
+                                "ai": { "type": "utility" }
+                            }
+                        ]
+                    },
+                    {
+                        "name": "slimes",
+                        "units": [
+                            {
+                                "name": "slime",
+                                "skills": [
+                                    {
+                                        "skill_name": "bounce",
+                                        "effects": [
+                                            { "name": "damage_skill_effect", "payload": { "min_damage": 10, "max_damage": 30 } }
+                                        ]
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let mut battle = data.create_battle(5);
+        battle.enable_replay().unwrap();
+        battle.run().unwrap();
+
+        let replay = battle.take_replay().unwrap();
+        assert!(!replay.steps.is_empty());
+
+        let replay = BattleReplay::from_json(&replay.to_json().unwrap()).unwrap();
+        let replayed = replay.play(new_processors()).unwrap();
+        assert_eq!(battle.turn(), replayed.turn());
+        assert_eq!(battle.winner(), replayed.winner());
+
+        let mut tampered = replay.clone();
+        let step = tampered.steps.len() / 2;
+        tampered.steps[step].values[0].insert("health".to_string(), 1.0);
+
+        assert!(matches!(
+            tampered.play(new_processors()),
+            Err(ReplayError::Divergence(divergence))
+                if divergence.step == step && divergence.attribute_name == "health"
+        ));
+
+        let mut unseeded = Battle::new(new_processors(), Default::default());
+        assert!(matches!(
+            unseeded.enable_replay(),
+            Err(ReplayError::NotSeeded)
+        ));
+    }
+}
@@ -0,0 +1,300 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{SeededNumberGenerator, SkillError};
+
+use super::{BattleData, BattleOutcome};
+
+///队伍的统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TeamReport {
+    pub name: String,
+    pub wins: usize,
+    pub win_rate: f32,
+    pub damage: f32,
+    pub heal: f32,
+}
+
+///技能或者单位的统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DamageReport {
+    pub name: String,
+    pub uses: usize,
+    pub hits: usize,
+    pub crits: usize,
+    pub damage: f32,
+    pub heal: f32,
+}
+
+impl DamageReport {
+    pub fn hit_rate(&self) -> f32 {
+        if self.uses == 0 {
+            0.0
+        } else {
+            self.hits as f32 / self.uses as f32
+        }
+    }
+
+    pub fn average_damage(&self) -> f32 {
+        if self.uses == 0 {
+            0.0
+        } else {
+            self.damage / self.uses as f32
+        }
+    }
+}
+
+///多场战斗的统计
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimulationReport {
+    pub battles: usize,
+    pub draws: usize,
+    pub average_turns: f32,
+    pub teams: Vec<TeamReport>,
+    pub skills: Vec<DamageReport>,
+    pub units: Vec<DamageReport>,
+}
+
+impl SimulationReport {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    ///输出为一张表格
+    ///
+    ///kind 为 summary、team、skill 或者 unit，count 分别为战斗场数、胜场、使用次数和行动次数，
+    ///rate 分别为平局率、胜率和命中率，summary 行的 average_damage 为平均回合数
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("kind,name,count,rate,damage,heal,average_damage,crits\n");
+
+        let draw_rate = if self.battles == 0 {
+            0.0
+        } else {
+            self.draws as f32 / self.battles as f32
+        };
+
+        let _ = writeln!(
+            csv,
+            "summary,battles,{},{},0,0,{},0",
+            self.battles, draw_rate, self.average_turns
+        );
+
+        for team in self.teams.iter() {
+            let _ = writeln!(
+                csv,
+                "team,{},{},{},{},{},0,0",
+                csv_field(&team.name),
+                team.wins,
+                team.win_rate,
+                team.damage,
+                team.heal
+            );
+        }
+
+        for (kind, reports) in [("skill", &self.skills), ("unit", &self.units)] {
+            for report in reports.iter() {
+                let _ = writeln!(
+                    csv,
+                    "{kind},{},{},{},{},{},{},{}",
+                    csv_field(&report.name),
+                    report.uses,
+                    report.hit_rate(),
+                    report.damage,
+                    report.heal,
+                    report.average_damage(),
+                    report.crits
+                );
+            }
+        }
+
+        csv
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+///使用同一份数据进行多场战斗
+///
+///每场战斗的种子由 seed 生成，相同的 seed 总是得到相同的统计
+pub struct BattleSimulation<'a> {
+    pub data: &'a BattleData,
+    pub seed: u64,
+}
+
+impl<'a> BattleSimulation<'a> {
+    pub fn new(data: &'a BattleData, seed: u64) -> Self {
+        Self { data, seed }
+    }
+
+    pub fn run(&self, battles: usize) -> Result<SimulationReport, SkillError> {
+        let mut seeds = SeededNumberGenerator::new(self.seed);
+
+        let mut report = SimulationReport {
+            teams: self
+                .data
+                .teams
+                .iter()
+                .map(|team| TeamReport {
+                    name: team.name.clone(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut skills = BTreeMap::new();
+        let mut units = BTreeMap::new();
+        let mut turns = 0;
+
+        for _ in 0..battles {
+            let mut battle = self.data.create_battle(seeds.next_u64());
+            let outcome = battle.run()?;
+
+            let unit_names = battle
+                .combatants()
+                .iter()
+                .map(|combatant| {
+                    (
+                        combatant.team,
+                        format!(
+                            "{}/{}",
+                            self.data.teams[combatant.team].name, combatant.name
+                        ),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            self.add_outcome(&mut report, &outcome, &unit_names, &mut skills, &mut units);
+            turns += outcome.turns as usize;
+        }
+
+        report.battles = battles;
+
+        if battles > 0 {
+            report.average_turns = turns as f32 / battles as f32;
+
+            for team in report.teams.iter_mut() {
+                team.win_rate = team.wins as f32 / battles as f32;
+            }
+        }
+
+        report.skills = skills.into_values().collect();
+        report.units = units.into_values().collect();
+
+        Ok(report)
+    }
+
+    fn add_outcome(
+        &self,
+        report: &mut SimulationReport,
+        outcome: &BattleOutcome,
+        unit_names: &[(usize, String)],
+        skills: &mut BTreeMap<String, DamageReport>,
+        units: &mut BTreeMap<String, DamageReport>,
+    ) {
+        match outcome.winner {
+            Some(team) => report.teams[team].wins += 1,
+            None => report.draws += 1,
+        }
+
+        for action in outcome.actions.iter() {
+            let (team, unit_name) = &unit_names[action.caster.0 as usize];
+
+            report.teams[*team].damage += action.damage;
+            report.teams[*team].heal += action.heal;
+
+            for (map, name) in [(&mut *skills, &action.skill_name), (&mut *units, unit_name)] {
+                let entry = map.entry(name.clone()).or_insert_with(|| DamageReport {
+                    name: name.clone(),
+                    ..Default::default()
+                });
+
+                entry.uses += 1;
+                entry.hits += action.hit as usize;
+                entry.crits += action.crit as usize;
+                entry.damage += action.damage;
+                entry.heal += action.heal;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{BattleData, BattleSimulation};
+
+    #[test]
+    fn test_battle_simulation() {
+        let data = BattleData::from_json(
+            r#"{
+                "teams": [
+                    {
+                        "name": "heroes",
+                        "units": [
+                            {
+                                "name": "knight",
+                                "skills": [
+                                    {
+                                        "skill_name": "slash",
+                                        "effects": [
+                                            { "name": "damage_skill_effect", "payload": { "min_damage": 40, "max_damage": 60, "hit_chance": 0.8 } }
+                                        ]
+                                    }
+                                ]
+                            }
+                        ]
+                    },
+                    {
+                        "name": "slimes",
+                        "units": [
+                            {
+                                "name": "slime",
+                                "skills": [
+                                    {
+                                        "skill_name": "bounce",
+                                        "effects": [
+                                            { "name": "damage_skill_effect", "payload": { "min_damage": 10, "max_damage": 30 } }
+                                        ]
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let report = BattleSimulation::new(&data, 3).run(50).unwrap();
+
+        assert_eq!(50, report.battles);
+        assert_eq!(
+            50,
+            report.teams.iter().map(|team| team.wins).sum::<usize>() + report.draws
+        );
+        assert!(report.teams[0].win_rate > report.teams[1].win_rate);
+        assert!(report.average_turns > 1.0);
+        assert_eq!(vec!["bounce", "slash"], {
+            report
+                .skills
+                .iter()
+                .map(|skill| skill.name.as_str())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!("heroes/knight", report.units[0].name);
+
+        assert_eq!(report, BattleSimulation::new(&data, 3).run(50).unwrap());
+
+        let csv = report.to_csv();
+        assert!(csv.starts_with("kind,name,count,rate"));
+        assert!(csv.contains("\nteam,heroes,"));
+        assert_eq!(1 + 1 + 2 + 2 + 2, csv.lines().count());
+    }
+}
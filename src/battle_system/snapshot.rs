@@ -0,0 +1,330 @@
+use std::{error::Error, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AiState, AttributeSet, BattleAi, NumberGeneratorContainer, NumberGeneratorContainerState,
+    NumberGeneratorError, SkillBook, SkillComponent, SkillEffectProcessorContainer, SkillStatus,
+};
+
+use super::{Battle, CombatantId, PassiveSkill, PendingSkill, Summon, SummonTemplate, ThreatTable};
+
+///快照中单位的状态，属性集合包括所有的修改器和约束
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CombatantSnapshot {
+    pub id: CombatantId,
+    pub name: String,
+    pub team: usize,
+    pub attribute_set: AttributeSet,
+    pub ai: AiState,
+    #[serde(default)]
+    pub passives: Vec<PassiveSkill>,
+    #[serde(default)]
+    pub pending: Option<PendingSkill>,
+    #[serde(default)]
+    pub skill_book: SkillBook,
+    #[serde(default)]
+    pub statuses: Vec<SkillStatus>,
+    #[serde(default)]
+    pub threat: ThreatTable,
+    #[serde(default)]
+    pub summon: Option<Summon>,
+    #[serde(default)]
+    pub removed: bool,
+}
+
+///战斗在两个回合之间的完整状态，单位按照行动顺序排列
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BattleSnapshot {
+    pub turn: u32,
+    pub max_turns: u32,
+    pub seed: Option<u64>,
+    pub combatants: Vec<CombatantSnapshot>,
+    ///行动顺序，为空时按照单位的顺序
+    #[serde(default)]
+    pub order: Vec<CombatantId>,
+    #[serde(default)]
+    pub summons: Vec<SummonTemplate>,
+    pub generators: NumberGeneratorContainerState,
+    pub ai_generators: NumberGeneratorContainerState,
+}
+
+///保存快照的错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotError {
+    ///随机数生成器无法保存，例如使用系统随机数的生成器
+    Generator(NumberGeneratorError),
+    ///单位的 AI 无法保存
+    Ai(CombatantId),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Generator(error) => write!(f, "generator can not be saved: {error}"),
+            SnapshotError::Ai(id) => write!(f, "ai of combatant {id} can not be saved"),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+impl From<NumberGeneratorError> for SnapshotError {
+    fn from(error: NumberGeneratorError) -> Self {
+        SnapshotError::Generator(error)
+    }
+}
+
+impl BattleSnapshot {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    ///恢复战斗，processors 需要与保存时注册的处理器一致
+    pub fn restore(&self, processors: SkillEffectProcessorContainer) -> Battle {
+        let mut battle = Battle::new(
+            processors,
+            NumberGeneratorContainer::from_state(&self.generators),
+        );
+        battle.ai_generators = NumberGeneratorContainer::from_state(&self.ai_generators);
+        battle.max_turns = self.max_turns;
+        battle.seed = self.seed;
+
+        for combatant in self.combatants.iter() {
+            let id = battle.add_boxed_combatant(
+                &combatant.name,
+                combatant.team,
+                SkillComponent {
+                    skill_book: combatant.skill_book.clone(),
+                    statuses: combatant.statuses.clone(),
+                    ..SkillComponent::new(combatant.attribute_set.clone())
+                },
+                vec![],
+                combatant.ai.to_battle_ai(),
+            );
+
+            for passive in combatant.passives.iter() {
+                battle.add_passive(id, passive.clone());
+            }
+
+            battle.combatants[id.0 as usize].pending = combatant.pending.clone();
+            battle.combatants[id.0 as usize].threat = combatant.threat.clone();
+            battle.combatants[id.0 as usize].summon = combatant.summon;
+            battle.combatants[id.0 as usize].removed = combatant.removed;
+        }
+
+        if !self.order.is_empty() {
+            battle.order = self.order.iter().map(|id| id.0 as usize).collect();
+        }
+
+        for template in self.summons.iter() {
+            battle.register_summon(template.clone());
+        }
+
+        battle.turn = self.turn;
+
+        battle
+    }
+}
+
+impl Battle {
+    ///保存战斗的状态，只能在两个回合之间调用
+    ///
+    ///使用系统随机数的生成器（例如 NumberGeneratorContainer::new）和自定义的 AI 无法保存，
+    ///此时返回错误，日志和回放不会被保存
+    pub fn snapshot(&self) -> Result<BattleSnapshot, SnapshotError> {
+        let mut combatants = Vec::with_capacity(self.combatants.len());
+
+        for (combatant, controller) in self.combatants.iter().zip(self.controllers.iter()) {
+            combatants.push(CombatantSnapshot {
+                id: combatant.id,
+                name: combatant.name.clone(),
+                team: combatant.team,
+                attribute_set: combatant.skill_component.attribute_set.clone(),
+                ai: controller.state().ok_or(SnapshotError::Ai(combatant.id))?,
+                passives: combatant.passives.clone(),
+                pending: combatant.pending.clone(),
+                skill_book: combatant.skill_component.skill_book.clone(),
+                statuses: combatant.skill_component.statuses.clone(),
+                threat: combatant.threat.clone(),
+                summon: combatant.summon,
+                removed: combatant.removed,
+            });
+        }
+
+        Ok(BattleSnapshot {
+            turn: self.turn,
+            max_turns: self.max_turns,
+            seed: self.seed,
+            combatants,
+            order: self.turn_order().collect(),
+            summons: self.summon_templates().cloned().collect(),
+            generators: self.generators.save_state()?,
+            ai_generators: self.ai_generators.save_state()?,
+        })
+    }
+
+    ///替换单位的 AI，单位不存在时返回 false
+    pub fn set_ai<T: BattleAi>(&mut self, id: CombatantId, ai: T) -> bool {
+        match self.controllers.get_mut(id.0 as usize) {
+            Some(controller) => {
+                *controller = Box::new(ai);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        AiAction, AiContext, AiState, Battle, BattleAi, BattleData, BattleSnapshot,
+        DamageSkillEffectProcessor, HealSkillEffectProcessor, NumberGeneratorContainer,
+        SkillEffectProcessorContainer, SnapshotError,
+    };
+
+    fn new_processors() -> SkillEffectProcessorContainer {
+        let mut processors = SkillEffectProcessorContainer::new();
+        processors.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+        processors.register_skill_effect_processor(HealSkillEffectProcessor);
+
+        processors
+    }
+
+    ///无法保存状态的 AI
+    struct IdleBattleAi;
+
+    impl BattleAi for IdleBattleAi {
+        fn choose_action(
+            &mut self,
+            _context: &AiContext,
+            _generators: &mut NumberGeneratorContainer,
+        ) -> Option<AiAction> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_battle_snapshot() {
+        let data = BattleData::from_json(
+            r#"{
+                "teams": [
+                    {
+                        "name": "heroes",
+                        "units": [
+                            {
+                                "name": "knight",
+                                "skills": [
+                                    {
+                                        "skill_name": "slash",
+                                        "effects": [
+                                            { "name": "damage_skill_effect", "payload": { "min_damage": 5, "max_damage": 15, "hit_chance": 0.8, "crit_chance": 0.3 } }
+                                        ]
+                                    },
+                                    {
+                                        "skill_name": "bash",
+                                        "effects": [
+                                            { "name": "damage_skill_effect", "payload": { "min_damage": 1, "max_damage": 3 } }
+                                        ]
+                                    }
+                                ],
+                                "ai": {
+                                    "type": "pattern",
+                                    "steps": [
+                                        { "skill_name": "slash" },
+                                        { "skill_name": "bash" },
+                                        { "skill_name": "bash" }
+                                    ]
+                                }
+                            }
+                        ]
+                    },
+                    {
+                        "name": "slimes",
+                        "units": [
+                            {
+                                "name": "slime",
+                                "skills": [
+                                    {
+                                        "skill_name": "bounce",
+                                        "effects": [
+                                            { "name": "damage_skill_effect", "payload": { "min_damage": 5, "max_damage": 10 } }
+                                        ]
+                                    }
+                                ],
+                                "ai": { "type": "utility" }
+                            },
+                            {
+                                "name": "bat",
+                                "skills": [
+                                    {
+                                        "skill_name": "bite",
+                                        "effects": [
+                                            { "name": "damage_skill_effect", "payload": { "min_damage": 1, "max_damage": 4 } }
+                                        ]
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let mut battle = data.create_battle(3);
+        battle.run_turn().unwrap();
+        battle.run_turn().unwrap();
+
+        let snapshot =
+            BattleSnapshot::from_json(&battle.snapshot().unwrap().to_json().unwrap()).unwrap();
+        assert!(matches!(
+            snapshot.combatants[0].ai,
+            AiState::Pattern { index: 2, .. }
+        ));
+        assert!(matches!(snapshot.combatants[1].ai, AiState::Utility { .. }));
+        assert!(matches!(snapshot.combatants[2].ai, AiState::Random { .. }));
+
+        let mut restored = snapshot.restore(new_processors());
+        assert_eq!(battle.turn(), restored.turn());
+
+        let outcome = battle.run().unwrap();
+        let restored_outcome = restored.run().unwrap();
+        assert_eq!(outcome, restored_outcome);
+
+        for (combatant, restored) in battle.combatants().iter().zip(restored.combatants()) {
+            assert_eq!(combatant.health(), restored.health());
+        }
+    }
+
+    #[test]
+    fn test_snapshot_errors() {
+        //系统随机数无法保存
+        let unseeded = Battle::new(new_processors(), Default::default());
+        assert!(matches!(
+            unseeded.snapshot(),
+            Err(SnapshotError::Generator(_))
+        ));
+
+        let data = BattleData::from_json(
+            r#"{
+                "teams": [
+                    { "name": "heroes", "units": [ { "name": "knight", "skills": [] } ] }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let mut battle = data.create_battle(3);
+        assert!(battle.snapshot().is_ok());
+
+        let id = battle.combatants()[0].id;
+        assert!(battle.set_ai(id, IdleBattleAi));
+        assert_eq!(battle.snapshot().unwrap_err(), SnapshotError::Ai(id));
+    }
+}
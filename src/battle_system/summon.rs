@@ -0,0 +1,435 @@
+use std::collections::BTreeMap;
+
+use bevy::ecs::{component::Component, world::World};
+use serde::{Deserialize, Serialize};
+
+use crate::{AttributeUtils, SkillContext, SkillEffectProcessor, SummonSkillEffectProcessor};
+
+use super::{Battle, CombatEvent, CombatantId, UnitData};
+
+///召唤物的模板
+///
+///scaling 中的属性会增加召唤者当前值乘以比例的基础值，例如 { "attack": 0.5 }
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SummonTemplate {
+    #[serde(flatten)]
+    pub unit: UnitData,
+    #[serde(default)]
+    pub scaling: BTreeMap<String, f32>,
+}
+
+///召唤物在 bevy 中的实体对应的战斗单位，实体同时带有召唤物的 SkillComponent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component)]
+pub struct SummonedCombatant {
+    pub id: CombatantId,
+    pub summoner: CombatantId,
+}
+
+///把非 Send 资源 Battle 中的召唤物同步为 world 中的实体
+pub fn sync_summons(world: &mut World) {
+    let Some(mut battle) = world.remove_non_send_resource::<Battle>() else {
+        return;
+    };
+
+    battle.sync_summon_entities(world);
+    world.insert_non_send_resource(battle);
+}
+
+///召唤物的来源和剩余的回合数
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Summon {
+    pub summoner: CombatantId,
+    ///None 表示一直存在到召唤者死亡
+    pub remaining_turns: Option<u32>,
+}
+
+impl Battle {
+    ///注册召唤物的模板，同名的模板会被替换
+    pub fn register_summon(&mut self, template: SummonTemplate) {
+        self.summon_templates
+            .insert(template.unit.name.clone(), template);
+    }
+
+    pub fn summon_templates(&self) -> impl Iterator<Item = &SummonTemplate> {
+        self.summon_templates.values()
+    }
+
+    ///使用名为 template 的模板为 summoner 召唤一个同队的单位
+    ///
+    ///召唤物从下一个回合开始在召唤者之后行动，lifetime 个回合之后或者召唤者死亡时消失，
+    ///召唤者不存在或者已经死亡、模板不存在时返回 None
+    ///
+    ///召唤物同时是 Battle 中的单位，sync_summon_entities 为它创建 bevy 的实体
+    pub fn summon(
+        &mut self,
+        summoner: CombatantId,
+        template: &str,
+        lifetime: Option<u32>,
+    ) -> Option<CombatantId> {
+        let owner = self.get(summoner).filter(|owner| owner.is_alive())?;
+        let template = self.summon_templates.get(template)?;
+
+        let mut unit = template.unit.clone();
+        let defaults = AttributeUtils::new_skill_attribute_set();
+
+        for (name, ratio) in template.scaling.iter() {
+            let value = owner
+                .skill_component
+                .attribute_set
+                .get_current_value(name.as_str())
+                .unwrap_or(0.0);
+            let base = unit
+                .attributes
+                .get(name)
+                .copied()
+                .or_else(|| defaults.get_base_value(name.as_str()))
+                .unwrap_or(0.0);
+
+            unit.attributes.insert(name.clone(), base + value * ratio);
+        }
+
+        let team = owner.team;
+        let skill_component = unit.to_skill_component(&self.constraints);
+        let ai = unit.ai.to_battle_ai();
+
+        let id = self.add_boxed_combatant(&unit.name, team, skill_component, vec![], ai);
+        let index = id.0 as usize;
+
+        for passive in unit.passives.iter() {
+            self.add_passive(id, passive.to_passive_skill());
+        }
+
+        self.combatants[index].summon = Some(Summon {
+            summoner,
+            remaining_turns: lifetime.filter(|turns| *turns > 0),
+        });
+
+        //放在召唤者和它之前的召唤物之后
+        self.order.retain(|order| *order != index);
+
+        if let Some(mut position) = self
+            .order
+            .iter()
+            .position(|order| *order == summoner.0 as usize)
+        {
+            position += 1;
+
+            while self.order.get(position).is_some_and(|order| {
+                self.combatants[*order]
+                    .summon
+                    .is_some_and(|summon| summon.summoner == summoner)
+            }) {
+                position += 1;
+            }
+
+            self.order.insert(position, index);
+        } else {
+            self.order.push(index);
+        }
+
+        Some(id)
+    }
+
+    ///为新的召唤物创建带有 SummonedCombatant 和 SkillComponent 的实体，
+    ///把已有实体的 SkillComponent 更新为召唤物当前的状态，删除消失的召唤物的实体
+    pub fn sync_summon_entities(&mut self, world: &mut World) {
+        for combatant in self.combatants.iter_mut() {
+            let Some(summon) = combatant.summon else {
+                continue;
+            };
+
+            match combatant.entity {
+                Some(entity) if combatant.removed => {
+                    world.despawn(entity);
+                    combatant.entity = None;
+                }
+                Some(entity) => {
+                    if let Ok(mut entity) = world.get_entity_mut(entity) {
+                        entity.insert(combatant.skill_component.clone());
+                    }
+                }
+                None if !combatant.removed => {
+                    let entity = world.spawn((
+                        SummonedCombatant {
+                            id: combatant.id,
+                            summoner: summon.summoner,
+                        },
+                        combatant.skill_component.clone(),
+                    ));
+
+                    combatant.entity = Some(entity.id());
+                }
+                None => {}
+            }
+        }
+    }
+
+    ///执行技能中的召唤效果，返回召唤物
+    pub(super) fn summon_from_context(
+        &mut self,
+        caster: CombatantId,
+        skill_context: &SkillContext,
+    ) -> Option<CombatantId> {
+        let result = skill_context.get_result(SummonSkillEffectProcessor::name())?;
+        let template = result.get_text(SummonSkillEffectProcessor::SUMMON)?;
+        let lifetime = result
+            .get_number(SummonSkillEffectProcessor::SUMMON_LIFETIME)
+            .unwrap_or(0.0) as u32;
+
+        self.summon(caster, template, Some(lifetime))
+    }
+
+    ///移除单位，移除的单位生命值为 0 并且不再行动，它的召唤物同时被移除
+    ///
+    ///为了保持标识符不变，单位仍然保留在战斗中，但是清除它的仇恨、状态和被动技能，
+    ///其它单位对它的仇恨和它施加的状态同时被清除
+    pub(super) fn dismiss(&mut self, index: usize) {
+        let combatant = &mut self.combatants[index];
+
+        if combatant.removed {
+            return;
+        }
+
+        let id = combatant.id;
+        combatant.removed = true;
+        combatant.pending = None;
+        combatant.threat = Default::default();
+        combatant.passives.clear();
+        combatant.skill_component.statuses.clear();
+        combatant.skill_component.attribute_set.set_current_value(
+            AttributeUtils::HEALTH,
+            0.0,
+            &self.constraints,
+        );
+        self.order.retain(|order| *order != index);
+
+        for other in 0..self.combatants.len() {
+            self.combatants[other].threat.remove(id);
+
+            let sourced = self.combatants[other]
+                .skill_component
+                .statuses
+                .iter()
+                .filter(|status| status.source == Some(id.0))
+                .map(|status| status.name.clone())
+                .collect::<Vec<_>>();
+
+            for name in sourced {
+                self.remove_status(self.combatants[other].id, &name);
+            }
+        }
+
+        self.push_event(CombatEvent::CombatantRemoved { id });
+        self.dismiss_summons(index);
+    }
+
+    ///移除 summoner 的所有召唤物
+    pub(super) fn dismiss_summons(&mut self, summoner: usize) {
+        for index in 0..self.combatants.len() {
+            let summoned = self.combatants[index]
+                .summon
+                .is_some_and(|summon| summon.summoner.0 as usize == summoner);
+
+            if summoned {
+                self.dismiss(index);
+            }
+        }
+    }
+
+    ///减少召唤物剩余的回合数，移除到期的召唤物
+    pub(super) fn tick_summons(&mut self) {
+        for index in 0..self.combatants.len() {
+            let Some(turns) = self.combatants[index]
+                .summon
+                .as_mut()
+                .and_then(|summon| summon.remaining_turns.as_mut())
+            else {
+                continue;
+            };
+
+            *turns = turns.saturating_sub(1);
+
+            if *turns == 0 {
+                self.dismiss(index);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::ecs::world::World;
+
+    use crate::{
+        AttributeUtils, Battle, CombatEvent, CombatantId, DamageSkillEffectProcessor,
+        NumberGeneratorContainer, PatternBattleAi, PatternStep, Skill, SkillComponent, SkillEffect,
+        SkillEffectProcessorContainer, SkillStatus, SummonSkillEffectProcessor, SummonTemplate,
+        SummonedCombatant, TargetSelector, UnitData,
+    };
+
+    fn summoned_entities(world: &mut World) -> Vec<(SummonedCombatant, f32)> {
+        let mut query = world.query::<(&SummonedCombatant, &SkillComponent)>();
+        let mut entities = query
+            .iter(world)
+            .map(|(summoned, skill_component)| {
+                let health = skill_component
+                    .attribute_set
+                    .get_current_value(AttributeUtils::HEALTH)
+                    .unwrap_or(0.0);
+
+                (*summoned, health)
+            })
+            .collect::<Vec<_>>();
+        entities.sort_by_key(|(summoned, _)| summoned.id);
+        entities
+    }
+
+    #[test]
+    fn test_summon() {
+        let mut processors = SkillEffectProcessorContainer::new();
+        processors.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+        processors.register_skill_effect_processor(SummonSkillEffectProcessor);
+
+        let mut battle = Battle::new(processors, NumberGeneratorContainer::seeded(1));
+        battle.enable_log();
+        battle.register_summon(SummonTemplate {
+            unit: serde_json::from_str::<UnitData>(
+                r#"{
+                    "name": "wolf",
+                    "attributes": { "max_health": 20 },
+                    "skills": [
+                        {
+                            "skill_name": "bite",
+                            "effects": [{ "name": "damage_skill_effect", "payload": { "min_damage": 13, "max_damage": 13 } }]
+                        }
+                    ],
+                    "ai": { "type": "pattern", "steps": [{ "skill_name": "bite" }] }
+                }"#,
+            )
+            .unwrap(),
+            scaling: [(AttributeUtils::MAX_HEALTH.to_string(), 0.5)].into(),
+        });
+
+        let howl = Skill::new(
+            "howl",
+            vec![
+                SkillEffect::new("summon_skill_effect")
+                    .with_value(SummonSkillEffectProcessor::TEMPLATE, "wolf")
+                    .with_value(SummonSkillEffectProcessor::LIFETIME, 2.0),
+            ],
+        );
+        let idle = Skill::new("idle", vec![SkillEffect::new("damage_skill_effect")]);
+
+        for (name, team, skill) in [
+            ("druid", 0, howl),
+            ("ogre", 1, idle.clone()),
+            ("imp", 1, idle),
+        ] {
+            battle.add_combatant(
+                name,
+                team,
+                SkillComponent::new(AttributeUtils::new_skill_attribute_set()),
+                vec![skill.clone()],
+                PatternBattleAi::new(vec![PatternStep {
+                    skill_name: skill.skill_name,
+                    selector: TargetSelector::First,
+                    ally: false,
+                }]),
+            );
+        }
+
+        let druid = CombatantId(0);
+        let ogre = CombatantId(1);
+        let wolf = CombatantId(3);
+
+        //召唤物在召唤者之后行动，最大生命值为 20 + 100 * 0.5
+        battle.run_turn().unwrap();
+        assert_eq!(
+            vec![druid, wolf, ogre, CombatantId(2)],
+            battle.turn_order().collect::<Vec<_>>()
+        );
+        let summoned = battle.get(wolf).unwrap();
+        assert_eq!(70.0, summoned.health());
+        assert_eq!(Some(druid), summoned.summon.map(|summon| summon.summoner));
+
+        //召唤物在 world 中有对应的实体
+        let mut world = World::new();
+        battle.sync_summon_entities(&mut world);
+        let wolf_entity = battle.get(wolf).unwrap().entity.unwrap();
+        assert_eq!(
+            Some(wolf),
+            world
+                .get::<SummonedCombatant>(wolf_entity)
+                .map(|summoned| summoned.id)
+        );
+        assert_eq!(
+            vec![(
+                SummonedCombatant {
+                    id: wolf,
+                    summoner: druid
+                },
+                70.0
+            )],
+            summoned_entities(&mut world)
+        );
+        assert!(battle.get(druid).unwrap().entity.is_none());
+
+        //咬 13 * 100 / 130 = 10
+        let actions = battle.run_turn().unwrap();
+        assert!(
+            actions
+                .iter()
+                .any(|action| action.caster == wolf && action.target == ogre)
+        );
+        assert_eq!(90.0, battle.get(ogre).unwrap().health());
+        assert!(battle.get(wolf).unwrap().is_alive());
+        assert_eq!(10.0, battle.threat(ogre).unwrap().get(wolf));
+
+        //再次同步时更新实体而不是创建新的实体
+        battle.sync_summon_entities(&mut world);
+        assert_eq!(Some(wolf_entity), battle.get(wolf).unwrap().entity);
+        assert_eq!(2, summoned_entities(&mut world).len());
+        assert!(battle.taunt(ogre, wolf, 3));
+
+        //持续 2 个回合，第三个回合开始时消失，新的召唤物加入
+        battle.run_turn().unwrap();
+        let expired = battle.get(wolf).unwrap();
+        assert!(expired.removed && !expired.is_alive());
+
+        //消失的召唤物的实体被删除
+        battle.sync_summon_entities(&mut world);
+        assert!(battle.get(wolf).unwrap().entity.is_none());
+        assert!(world.get_entity(wolf_entity).is_err());
+        assert!(
+            summoned_entities(&mut world)
+                .iter()
+                .all(|(summoned, _)| summoned.id != wolf)
+        );
+
+        //消失的召唤物的仇恨和它施加的嘲讽被清除
+        let ogre_combatant = battle.get(ogre).unwrap();
+        assert!(ogre_combatant.threat.iter().all(|(id, _)| id != wolf));
+        assert!(
+            !ogre_combatant
+                .skill_component
+                .has_status(SkillStatus::TAUNT)
+        );
+        assert!(battle.log().unwrap().iter().any(|entry| matches!(
+            entry.event,
+            CombatEvent::CombatantRemoved { id } if id == wolf
+        )));
+        assert_eq!(
+            vec![druid, CombatantId(4), CombatantId(5), ogre, CombatantId(2)],
+            battle.turn_order().collect::<Vec<_>>()
+        );
+
+        //召唤者死亡时召唤物一起消失
+        battle.change_health(0, 1, 1000.0, 0.0);
+        assert_eq!(
+            vec![druid, ogre, CombatantId(2)],
+            battle.turn_order().collect::<Vec<_>>()
+        );
+        battle.sync_summon_entities(&mut world);
+        assert!(summoned_entities(&mut world).is_empty());
+    }
+}
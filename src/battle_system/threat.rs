@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::SkillStatus;
+
+use super::{Battle, Cast, CombatantId};
+
+///治疗产生的仇恨相对于治疗量的比例
+pub const HEAL_THREAT_RATIO: f32 = 0.5;
+
+///单位对其它单位的仇恨
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThreatTable {
+    threat: BTreeMap<CombatantId, f32>,
+}
+
+impl ThreatTable {
+    pub fn get(&self, id: CombatantId) -> f32 {
+        self.threat.get(&id).copied().unwrap_or(0.0)
+    }
+
+    pub fn add(&mut self, id: CombatantId, threat: f32) {
+        if threat > 0.0 {
+            *self.threat.entry(id).or_default() += threat;
+        }
+    }
+
+    pub fn remove(&mut self, id: CombatantId) -> Option<f32> {
+        self.threat.remove(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (CombatantId, f32)> {
+        self.threat.iter().map(|(id, threat)| (*id, *threat))
+    }
+
+    ///满足 filter 的单位中仇恨最高的，仇恨相同时选择标识符较小的
+    pub fn highest(&self, filter: impl Fn(CombatantId) -> bool) -> Option<CombatantId> {
+        self.iter()
+            .filter(|(id, _)| filter(*id))
+            .fold(
+                None,
+                |best: Option<(CombatantId, f32)>, (id, threat)| match best {
+                    Some((_, best_threat)) if best_threat >= threat => best,
+                    _ => Some((id, threat)),
+                },
+            )
+            .map(|(id, _)| id)
+    }
+}
+
+impl Battle {
+    pub fn threat(&self, id: CombatantId) -> Option<&ThreatTable> {
+        self.get(id).map(|combatant| &combatant.threat)
+    }
+
+    ///单位的存活敌方中仇恨最高的单位
+    pub fn highest_threat(&self, id: CombatantId) -> Option<CombatantId> {
+        let combatant = self.get(id)?;
+
+        combatant.threat.highest(|source| {
+            self.get(source)
+                .is_some_and(|source| source.team != combatant.team && source.is_alive())
+        })
+    }
+
+    ///为 id 添加 turns 个回合的嘲讽状态，使它只能攻击 source，单位不存在时返回 false
+    pub fn taunt(&mut self, id: CombatantId, source: CombatantId, turns: u32) -> bool {
+        if turns == 0 || self.get(source).is_none() {
+            return false;
+        }
+
+        self.add_status(id, SkillStatus::taunt(source.0, Some(turns)))
+    }
+
+    ///嘲讽单位的下标，嘲讽的来源死亡时不再生效
+    pub(super) fn taunted_by(&self, index: usize) -> Option<usize> {
+        let source = self.combatants[index]
+            .skill_component
+            .status(SkillStatus::TAUNT)?
+            .source? as usize;
+
+        self.combatants
+            .get(source)
+            .is_some_and(|source| source.is_alive())
+            .then_some(source)
+    }
+
+    ///按照技能的伤害、治疗和嘲讽效果更新仇恨
+    ///
+    ///受到伤害的单位增加对攻击者的仇恨，被治疗单位的敌方增加对治疗者的仇恨
+    pub(super) fn record_threat(&mut self, cast: &Cast) {
+        let action = &cast.action;
+        let caster = action.caster.0 as usize;
+        let target = action.target.0 as usize;
+
+        if self.combatants[caster].team != self.combatants[target].team {
+            self.combatants[target]
+                .threat
+                .add(action.caster, action.damage + cast.threat);
+            self.combatants[caster]
+                .threat
+                .add(action.target, action.caster_damage);
+
+            if cast.taunt_turns > 0 {
+                self.taunt(action.target, action.caster, cast.taunt_turns);
+            }
+        }
+
+        for (healed, heal) in [(target, action.heal), (caster, action.caster_heal)] {
+            if heal <= 0.0 {
+                continue;
+            }
+
+            let team = self.combatants[healed].team;
+
+            for combatant in self.combatants.iter_mut() {
+                if combatant.team != team && combatant.is_alive() {
+                    combatant
+                        .threat
+                        .add(action.caster, heal * HEAL_THREAT_RATIO);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        AttributeUtils, Battle, CombatantId, DamageSkillEffectProcessor, HealSkillEffectProcessor,
+        NumberGeneratorContainer, PatternBattleAi, PatternStep, Skill, SkillComponent, SkillEffect,
+        SkillEffectProcessorContainer, SkillStatus, SkillUsage, TargetSelector,
+        TauntSkillEffectProcessor,
+    };
+
+    fn pattern(skill_name: &str, selector: TargetSelector, ally: bool) -> PatternBattleAi {
+        PatternBattleAi::new(vec![PatternStep {
+            skill_name: skill_name.to_string(),
+            selector,
+            ally,
+        }])
+    }
+
+    #[test]
+    fn test_threat_and_taunt() {
+        let mut processors = SkillEffectProcessorContainer::new();
+        processors.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+        processors.register_skill_effect_processor(HealSkillEffectProcessor);
+        processors.register_skill_effect_processor(TauntSkillEffectProcessor);
+
+        let mut battle = Battle::new(processors, NumberGeneratorContainer::seeded(1));
+        battle.enable_log();
+
+        let poke = Skill::new(
+            "poke",
+            vec![
+                SkillEffect::new("damage_skill_effect")
+                    .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, 13.0)
+                    .with_value(DamageSkillEffectProcessor::MAX_DAMAGE, 13.0),
+            ],
+        );
+        let mend = Skill::new(
+            "mend",
+            vec![
+                SkillEffect::new("heal_skill_effect")
+                    .with_value(HealSkillEffectProcessor::MIN_HEAL, 20.0)
+                    .with_value(HealSkillEffectProcessor::MAX_HEAL, 20.0),
+            ],
+        );
+        let provoke = Skill::new(
+            "provoke",
+            vec![
+                SkillEffect::new("taunt_skill_effect")
+                    .with_value(TauntSkillEffectProcessor::THREAT, 30.0)
+                    .with_value(TauntSkillEffectProcessor::TURNS, 2.0),
+            ],
+        );
+
+        let units = [
+            (
+                "knight",
+                0,
+                poke.clone(),
+                TargetSelector::HighestHealth,
+                false,
+            ),
+            ("priest", 0, mend, TargetSelector::LowestHealth, true),
+            ("ogre", 1, poke, TargetSelector::HighestThreat, false),
+        ];
+
+        for (name, team, skill, selector, ally) in units {
+            battle.add_combatant(
+                name,
+                team,
+                SkillComponent::new(AttributeUtils::new_skill_attribute_set()),
+                vec![skill.clone()],
+                pattern(&skill.skill_name, selector, ally),
+            );
+        }
+
+        let knight = CombatantId(0);
+        let priest = CombatantId(1);
+        let ogre = CombatantId(2);
+
+        //骑士造成 10 点伤害，牧师治疗骑士 10 点，食人魔攻击仇恨最高的骑士
+        let actions = battle.run_turn().unwrap();
+        let threat = battle.threat(ogre).unwrap();
+        assert_eq!(10.0, threat.get(knight));
+        assert_eq!(0.0, threat.get(priest));
+        assert_eq!(Some(knight), battle.highest_threat(ogre));
+        assert_eq!(knight, actions[2].target);
+
+        //牧师治疗受伤的骑士，产生 5 点仇恨
+        battle.run_turn().unwrap();
+        assert_eq!(5.0, battle.threat(ogre).unwrap().get(priest));
+
+        //嘲讽强制食人魔攻击牧师
+        battle.combatants[1]
+            .skill_component
+            .skill_book
+            .learn_and_equip(provoke, SkillUsage::default())
+            .unwrap();
+        battle.set_ai(priest, pattern("provoke", TargetSelector::First, false));
+        let actions = battle.run_turn().unwrap();
+        assert_eq!(priest, actions[2].target);
+        assert_eq!(35.0, battle.threat(ogre).unwrap().get(priest));
+        let taunt = battle
+            .get(ogre)
+            .unwrap()
+            .skill_component
+            .status(SkillStatus::TAUNT)
+            .unwrap();
+        assert_eq!(Some(priest.0), taunt.source);
+        assert_eq!(Some(2), taunt.remaining_turns);
+
+        battle.set_ai(priest, pattern("mend", TargetSelector::First, true));
+        battle.run_turn().unwrap();
+        battle.run_turn().unwrap();
+        assert!(
+            !battle
+                .get(ogre)
+                .unwrap()
+                .skill_component
+                .has_status(SkillStatus::TAUNT)
+        );
+    }
+}
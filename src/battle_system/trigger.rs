@@ -0,0 +1,621 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AttributeUtils, Skill, SkillError, SkillTag};
+
+use super::{Battle, BattleAction, Cast, CombatantId};
+
+///一个行动最多连锁触发的被动技能层数
+pub const MAX_TRIGGER_DEPTH: u32 = 8;
+
+///被动技能的触发条件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SkillTrigger {
+    ///回合开始时
+    TurnStart,
+    ///受到命中的伤害时，来源为攻击者
+    Hit,
+    ///造成暴击时，来源为被暴击的目标
+    Crit,
+    ///友方单位死亡时，来源为死亡的单位
+    AllyDeath,
+    ///生命值降到最大生命值的 ratio 以下时，来源为攻击者
+    HealthBelow { ratio: f32 },
+}
+
+///被动技能的目标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerTarget {
+    ///拥有被动技能的单位
+    #[default]
+    Owner,
+    ///触发事件的来源
+    Source,
+}
+
+///满足触发条件时自动执行的技能
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PassiveSkill {
+    pub skill: Skill,
+    pub trigger: SkillTrigger,
+    #[serde(default)]
+    pub target: TriggerTarget,
+    ///触发事件的技能需要带有的标签，None 表示不限制，回合开始时不检查
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_tag: Option<SkillTag>,
+}
+
+///触发的行动
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reaction {
+    ///第 n 个被动技能
+    Passive(usize),
+    ///使用第一个可以使用的技能反击来源
+    Counter,
+}
+
+///一次被动技能或者反击的触发
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Firing {
+    owner: usize,
+    reaction: Reaction,
+    source: usize,
+}
+
+impl Battle {
+    ///为单位添加被动技能，单位不存在时返回 false
+    pub fn add_passive(&mut self, id: CombatantId, passive: PassiveSkill) -> bool {
+        match self.combatants.get_mut(id.0 as usize) {
+            Some(combatant) => {
+                combatant.passives.push(passive);
+                true
+            }
+            None => false,
+        }
+    }
+
+    ///单位反击 target 时使用的技能，为第一个可以使用并且可以选择 target 的技能
+    fn counter_skill(&self, owner: usize, target: usize) -> Option<Skill> {
+        let combatant = &self.combatants[owner];
+
+        combatant
+            .skills()
+            .find(|skill| {
+                combatant.skill_component.can_use(skill).is_ok()
+                    && self.is_valid_target(combatant.id, skill, self.combatants[target].id)
+            })
+            .cloned()
+    }
+
+    fn firings(
+        &self,
+        owner: usize,
+        source: usize,
+        tags: Option<&[SkillTag]>,
+        filter: impl Fn(&SkillTrigger) -> bool,
+    ) -> impl Iterator<Item = Firing> {
+        self.combatants[owner]
+            .passives
+            .iter()
+            .enumerate()
+            .filter(move |(_, passive)| {
+                let tagged = match (passive.source_tag, tags) {
+                    (Some(tag), Some(tags)) => tags.contains(&tag),
+                    _ => true,
+                };
+
+                tagged && filter(&passive.trigger)
+            })
+            .map(move |(passive, _)| Firing {
+                owner,
+                reaction: Reaction::Passive(passive),
+                source,
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    pub(super) fn turn_start_firings(&self) -> Vec<Firing> {
+        (0..self.combatants.len())
+            .flat_map(|owner| {
+                self.firings(owner, owner, None, |trigger| {
+                    matches!(trigger, SkillTrigger::TurnStart)
+                })
+            })
+            .collect()
+    }
+
+    ///行动之后触发的被动技能和反击，按照受击、暴击、反击、生命值、友方死亡的顺序
+    pub(super) fn action_firings(&self, cast: &Cast) -> Vec<Firing> {
+        let caster = cast.action.caster.0 as usize;
+        let target = cast.action.target.0 as usize;
+        let tags = Some(cast.tags.as_slice());
+        let mut firings = vec![];
+
+        if cast.action.hit && cast.action.damage > 0.0 {
+            firings.extend(self.firings(target, caster, tags, |trigger| {
+                matches!(trigger, SkillTrigger::Hit)
+            }));
+        }
+
+        if cast.action.crit {
+            firings.extend(self.firings(caster, target, tags, |trigger| {
+                matches!(trigger, SkillTrigger::Crit)
+            }));
+        }
+
+        if cast.action.counter {
+            firings.push(Firing {
+                owner: target,
+                reaction: Reaction::Counter,
+                source: caster,
+            });
+        }
+
+        for change in cast.changes.iter() {
+            let max_health = self.combatants[change.index]
+                .skill_component
+                .attribute_set
+                .get_current_value(AttributeUtils::MAX_HEALTH);
+
+            if let Some(max_health) = max_health {
+                firings.extend(self.firings(change.index, change.source, tags, |trigger| {
+                    match trigger {
+                        SkillTrigger::HealthBelow { ratio } => {
+                            let threshold = ratio * max_health;
+                            change.previous_health > threshold && change.health <= threshold
+                        }
+                        _ => false,
+                    }
+                }));
+            }
+        }
+
+        for change in cast.changes.iter() {
+            if change.previous_health > 0.0 && change.health <= 0.0 {
+                let team = self.combatants[change.index].team;
+
+                for owner in 0..self.combatants.len() {
+                    if owner != change.index && self.combatants[owner].team == team {
+                        firings.extend(self.firings(owner, change.index, tags, |trigger| {
+                            matches!(trigger, SkillTrigger::AllyDeath)
+                        }));
+                    }
+                }
+            }
+        }
+
+        firings
+    }
+
+    ///依次执行触发的被动技能和反击，触发的行动可以继续触发
+    ///
+    ///同一个被动技能或者同一个单位的反击在一次连锁中最多触发一次，连锁最多 MAX_TRIGGER_DEPTH 层，死亡的单位不会触发
+    pub(super) fn trigger_reactions(
+        &mut self,
+        firings: Vec<Firing>,
+        actions: &mut Vec<BattleAction>,
+    ) -> Result<(), SkillError> {
+        let mut queue = firings
+            .into_iter()
+            .map(|firing| (firing, 1))
+            .collect::<VecDeque<_>>();
+        let mut fired = vec![];
+
+        while let Some((firing, depth)) = queue.pop_front() {
+            if depth > MAX_TRIGGER_DEPTH
+                || fired.contains(&(firing.owner, firing.reaction))
+                || !self.combatants[firing.owner].is_alive()
+            {
+                continue;
+            }
+
+            let owner = &self.combatants[firing.owner];
+            let (skill, target) = match firing.reaction {
+                Reaction::Passive(passive) => {
+                    let passive = &owner.passives[passive];
+                    let target = match passive.target {
+                        TriggerTarget::Owner => firing.owner,
+                        TriggerTarget::Source => firing.source,
+                    };
+
+                    (passive.skill.clone(), target)
+                }
+                Reaction::Counter => match self.counter_skill(firing.owner, firing.source) {
+                    Some(skill) if self.combatants[firing.source].is_alive() => {
+                        (skill, firing.source)
+                    }
+                    _ => continue,
+                },
+            };
+
+            //被状态禁用或者目标无效的被动技能和反击不会触发
+            if self.combatants[firing.owner]
+                .skill_component
+                .check_tags(&skill)
+                .is_err()
+                || !self.is_valid_target(
+                    self.combatants[firing.owner].id,
+                    &skill,
+                    self.combatants[target].id,
+                )
+            {
+                continue;
+            }
+
+            //反击与主动使用技能一样扣除消耗并开始冷却
+            if firing.reaction == Reaction::Counter
+                && self.combatants[firing.owner]
+                    .skill_component
+                    .use_skill(&skill, &self.constraints)
+                    .is_err()
+            {
+                continue;
+            }
+
+            fired.push((firing.owner, firing.reaction));
+
+            let cast = self.cast(firing.owner, &skill, target, true)?;
+
+            queue.extend(
+                self.action_firings(&cast)
+                    .into_iter()
+                    .map(|firing| (firing, depth + 1)),
+            );
+            actions.push(cast.action);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        Attribute, AttributeUtils, Battle, BattleAction, CombatantId, CounterSkillEffectProcessor,
+        DamageSkillEffectProcessor, HealSkillEffectProcessor, LifestealSkillEffectProcessor,
+        NumberGeneratorContainer, PassiveSkill, PatternBattleAi, PatternStep,
+        ReflectSkillEffectProcessor, Skill, SkillComponent, SkillEffect,
+        SkillEffectProcessorContainer, SkillTags, SkillTrigger, SkillUsage, TargetSelector,
+        ThornsSkillEffectProcessor, TriggerTarget,
+    };
+
+    fn damage(value: f32) -> SkillEffect {
+        SkillEffect::new("damage_skill_effect")
+            .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, value)
+            .with_value(DamageSkillEffectProcessor::MAX_DAMAGE, value)
+    }
+
+    fn thorns() -> PassiveSkill {
+        PassiveSkill {
+            skill: Skill::new("thorns", vec![damage(5.0)]),
+            trigger: SkillTrigger::Hit,
+            target: TriggerTarget::Source,
+            source_tag: None,
+        }
+    }
+
+    fn new_battle(skill: Skill) -> Battle {
+        let mut processors = SkillEffectProcessorContainer::new();
+        processors.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+        processors.register_skill_effect_processor(HealSkillEffectProcessor);
+        processors.register_skill_effect_processor(LifestealSkillEffectProcessor);
+        processors.register_reaction_processor(ReflectSkillEffectProcessor::default());
+        processors.register_reaction_processor(ThornsSkillEffectProcessor::default());
+        processors.register_reaction_processor(CounterSkillEffectProcessor);
+
+        let mut battle = Battle::new(processors, NumberGeneratorContainer::seeded(1));
+
+        for (name, team) in [("knight", 0), ("cactus", 1)] {
+            battle.add_combatant(
+                name,
+                team,
+                SkillComponent::new(AttributeUtils::new_skill_attribute_set()),
+                vec![skill.clone()],
+                PatternBattleAi::new(vec![PatternStep {
+                    skill_name: skill.skill_name.clone(),
+                    selector: TargetSelector::First,
+                    ally: false,
+                }]),
+            );
+        }
+
+        battle
+    }
+
+    fn insert_attribute(battle: &mut Battle, id: CombatantId, name: &str, value: f32) {
+        let mut attribute = Attribute::default();
+        attribute.attribute_name = name.into();
+        attribute.set_base_value(value);
+
+        battle.combatants[id.0 as usize]
+            .skill_component
+            .attribute_set
+            .insert_attribute(attribute);
+    }
+
+    #[test]
+    fn test_passive_trigger() {
+        let mut battle = new_battle(Skill::new("poke", vec![damage(10.0)]));
+
+        let knight = CombatantId(0);
+        let cactus = CombatantId(1);
+
+        assert!(battle.add_passive(knight, thorns()));
+        assert!(battle.add_passive(cactus, thorns()));
+        assert!(battle.add_passive(cactus, heal("regenerate", SkillTrigger::TurnStart)));
+        //只有被法术命中时触发
+        assert!(battle.add_passive(
+            knight,
+            PassiveSkill {
+                source_tag: Some(SkillTags::MAGIC.into()),
+                ..thorns()
+            }
+        ));
+        assert!(!battle.add_passive(CombatantId(2), thorns()));
+
+        let actions = battle.run_turn().unwrap();
+        let names = actions
+            .iter()
+            .map(|action| (action.skill_name.as_str(), action.triggered))
+            .collect::<Vec<_>>();
+
+        //两个荆棘效果互相反射时每个只触发一次
+        assert_eq!(
+            vec![
+                ("regenerate", true),
+                ("poke", false),
+                ("thorns", true),
+                ("thorns", true),
+                ("poke", false),
+                ("thorns", true),
+                ("thorns", true),
+            ],
+            names
+        );
+
+        for id in [knight, cactus] {
+            let damage = actions
+                .iter()
+                .filter(|action| action.target == id)
+                .map(|action| action.damage)
+                .sum::<f32>();
+
+            assert!(damage > 0.0);
+            assert_eq!(battle.get(id).unwrap().health(), 100.0 - damage);
+        }
+    }
+
+    #[test]
+    fn test_reactive_effects() {
+        let mut skill = Skill::new("poke", vec![damage(10.0)]);
+        skill.effcts.push(
+            SkillEffect::new("lifesteal_skill_effect")
+                .with_value(LifestealSkillEffectProcessor::RATIO, 0.5),
+        );
+
+        //荆棘和反击由受到命中的单位的属性决定，荆棘的伤害经过攻击者的防御 3 * 100 / 130
+        let mut battle = new_battle(skill);
+        for id in [CombatantId(0), CombatantId(1)] {
+            insert_attribute(&mut battle, id, AttributeUtils::THORNS, 3.0);
+            insert_attribute(&mut battle, id, AttributeUtils::COUNTER_CHANCE, 1.0);
+        }
+
+        let actions = battle.run_turn().unwrap();
+
+        //反击的反击只触发一次
+        assert_eq!(
+            vec![false, true, true, false, true, true],
+            actions
+                .iter()
+                .map(|action| action.triggered)
+                .collect::<Vec<_>>()
+        );
+        assert!(
+            actions
+                .iter()
+                .all(|action| action.counter && action.caster_damage == 2.0)
+        );
+
+        for combatant in battle.combatants() {
+            let taken = actions
+                .iter()
+                .filter(|action| action.target == combatant.id)
+                .map(|action| action.damage)
+                .sum::<f32>();
+            let recoil = actions
+                .iter()
+                .filter(|action| action.caster == combatant.id)
+                .map(|action| action.caster_damage - action.caster_heal)
+                .sum::<f32>();
+
+            assert_eq!(100.0 - taken - recoil, combatant.health());
+        }
+    }
+
+    #[test]
+    fn test_counter_uses_skill_book() {
+        let mut battle = new_battle(Skill::new("poke", vec![damage(10.0)]));
+
+        let knight = CombatantId(0);
+        let cactus = CombatantId(1);
+
+        insert_attribute(&mut battle, cactus, AttributeUtils::REFLECT, 0.5);
+        insert_attribute(&mut battle, cactus, AttributeUtils::COUNTER_CHANCE, 1.0);
+
+        //反击消耗全部能量，之后仙人掌无法再使用技能
+        battle.combatants[1]
+            .skill_component
+            .skill_book
+            .get_mut("poke")
+            .unwrap()
+            .usage = SkillUsage::default().with_cost(AttributeUtils::POWER, 10.0);
+
+        let actions = battle.run_turn().unwrap();
+        assert_eq!(
+            vec![(knight, false), (cactus, true)],
+            actions
+                .iter()
+                .map(|action| (action.caster, action.triggered))
+                .collect::<Vec<_>>()
+        );
+        assert!(actions[0].counter);
+        assert_eq!(
+            (actions[0].damage * 0.5 * 100.0 / 130.0).floor(),
+            actions[0].caster_damage
+        );
+        assert_eq!(
+            Some(0.0),
+            battle
+                .get(cactus)
+                .unwrap()
+                .skill_component
+                .attribute_set
+                .get_current_value(AttributeUtils::POWER)
+        );
+
+        //没有能量时不会反击
+        let actions = battle.run_turn().unwrap();
+        assert_eq!(1, actions.len());
+        assert!(actions[0].counter);
+    }
+
+    fn heal(name: &str, trigger: SkillTrigger) -> PassiveSkill {
+        PassiveSkill {
+            skill: Skill::new(
+                name,
+                vec![
+                    SkillEffect::new("heal_skill_effect")
+                        .with_value(HealSkillEffectProcessor::MIN_HEAL, 1.0)
+                        .with_value(HealSkillEffectProcessor::MAX_HEAL, 1.0),
+                ],
+            ),
+            trigger,
+            target: TriggerTarget::Owner,
+            source_tag: None,
+        }
+    }
+
+    fn set_health(battle: &mut Battle, id: CombatantId, health: f32) {
+        battle.combatants[id.0 as usize]
+            .skill_component
+            .attribute_set
+            .set_current_value(AttributeUtils::HEALTH, health, &battle.constraints);
+    }
+
+    fn casts(actions: &[BattleAction]) -> Vec<(&str, CombatantId, bool)> {
+        actions
+            .iter()
+            .map(|action| (action.skill_name.as_str(), action.caster, action.triggered))
+            .collect()
+    }
+
+    #[test]
+    fn test_crit_trigger() {
+        let mut battle = new_battle(Skill::new(
+            "poke",
+            vec![damage(10.0).with_value(DamageSkillEffectProcessor::CRIT_CHANCE, 1.0)],
+        ));
+
+        let knight = CombatantId(0);
+        let cactus = CombatantId(1);
+
+        //暴击时对被暴击的目标追加伤害，追加的伤害没有暴击，不会再次触发
+        battle.add_passive(
+            knight,
+            PassiveSkill {
+                skill: Skill::new("rend", vec![damage(1.0)]),
+                trigger: SkillTrigger::Crit,
+                target: TriggerTarget::Source,
+                source_tag: None,
+            },
+        );
+
+        let actions = battle.run_turn().unwrap();
+        assert_eq!(
+            vec![
+                ("poke", knight, false),
+                ("rend", knight, true),
+                ("poke", cactus, false)
+            ],
+            casts(&actions)
+        );
+        assert!(actions[0].crit && !actions[1].crit);
+        assert_eq!(cactus, actions[1].target);
+    }
+
+    #[test]
+    fn test_ally_death_trigger() {
+        let skill = Skill::new("poke", vec![damage(10.0)]);
+        let mut battle = new_battle(skill.clone());
+
+        let knight = CombatantId(0);
+        let cactus = CombatantId(1);
+        let sprout = battle.add_combatant(
+            "sprout",
+            1,
+            SkillComponent::new(AttributeUtils::new_skill_attribute_set()),
+            vec![skill],
+            PatternBattleAi::new(vec![PatternStep {
+                skill_name: "poke".to_string(),
+                selector: TargetSelector::First,
+                ally: false,
+            }]),
+        );
+
+        //只有友方单位死亡时触发，敌方单位死亡不会触发
+        battle.add_passive(sprout, heal("mourn", SkillTrigger::AllyDeath));
+        battle.add_passive(knight, heal("gloat", SkillTrigger::AllyDeath));
+        set_health(&mut battle, cactus, 5.0);
+
+        let actions = battle.run_turn().unwrap();
+        assert_eq!(
+            vec![
+                ("poke", knight, false),
+                ("mourn", sprout, true),
+                ("poke", sprout, false)
+            ],
+            casts(&actions)
+        );
+        assert!(!battle.get(cactus).unwrap().is_alive());
+    }
+
+    #[test]
+    fn test_health_below_trigger() {
+        let mut battle = new_battle(Skill::new("poke", vec![damage(10.0)]));
+
+        let knight = CombatantId(0);
+        let cactus = CombatantId(1);
+
+        battle.add_passive(
+            cactus,
+            heal("bandage", SkillTrigger::HealthBelow { ratio: 0.5 }),
+        );
+        set_health(&mut battle, cactus, 53.0);
+
+        //生命值从阈值以上降到阈值以下时触发
+        let actions = battle.run_turn().unwrap();
+        assert_eq!(
+            vec![
+                ("poke", knight, false),
+                ("bandage", cactus, true),
+                ("poke", cactus, false)
+            ],
+            casts(&actions)
+        );
+        assert_eq!(
+            53.0 - actions[0].damage + 1.0,
+            battle.get(cactus).unwrap().health()
+        );
+
+        //已经在阈值以下时不会再次触发
+        let actions = battle.run_turn().unwrap();
+        assert_eq!(
+            vec![("poke", knight, false), ("poke", cactus, false)],
+            casts(&actions)
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! 无界面的战斗模拟器，用于平衡技能数据
+//!
+//! cargo run --release --bin simulate -- assets/battles/example.json --battles 10000 --format csv
+
+use std::{env, fs, process::ExitCode};
+
+use turn_base_demo::{BattleData, BattleSimulation};
+
+const USAGE: &str =
+    "usage: simulate <battle.json> [--battles N] [--seed N] [--format json|csv] [--output PATH]";
+
+enum Format {
+    Json,
+    Csv,
+}
+
+struct Options {
+    path: String,
+    battles: usize,
+    seed: u64,
+    format: Format,
+    output: Option<String>,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut args = env::args().skip(1);
+
+    let mut options = Options {
+        path: String::new(),
+        battles: 1000,
+        seed: 0,
+        format: Format::Json,
+        output: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {arg}"))
+        };
+
+        match arg.as_str() {
+            "--battles" => {
+                options.battles = value()?
+                    .parse()
+                    .map_err(|e| format!("invalid --battles: {e}"))?
+            }
+            "--seed" => {
+                options.seed = value()?
+                    .parse()
+                    .map_err(|e| format!("invalid --seed: {e}"))?
+            }
+            "--format" => {
+                options.format = match value()?.as_str() {
+                    "json" => Format::Json,
+                    "csv" => Format::Csv,
+                    format => return Err(format!("unknown format: {format}")),
+                }
+            }
+            "--output" => options.output = Some(value()?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if options.path.is_empty() => options.path = arg,
+            _ => return Err(format!("unexpected argument: {arg}")),
+        }
+    }
+
+    if options.path.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    Ok(options)
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let json = fs::read_to_string(&options.path)
+        .map_err(|e| format!("failed to read {}: {e}", options.path))?;
+    let data = BattleData::from_json(&json)
+        .map_err(|e| format!("failed to parse {}: {e}", options.path))?;
+
+    let report = BattleSimulation::new(&data, options.seed)
+        .run(options.battles)
+        .map_err(|e| format!("battle failed: {e:?}"))?;
+
+    let output = match options.format {
+        Format::Json => report.to_json().map_err(|e| e.to_string())?,
+        Format::Csv => report.to_csv(),
+    };
+
+    match options.output.as_ref() {
+        Some(path) => fs::write(path, output).map_err(|e| format!("failed to write {path}: {e}")),
+        None => {
+            println!("{output}");
+            Ok(())
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let result = parse_options().and_then(|options| run(&options));
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
+    }
+}
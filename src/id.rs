@@ -0,0 +1,226 @@
+use std::sync::{PoisonError, RwLock};
+
+use bevy::platform::collections::HashMap;
+
+///名称注册表，将字符串映射为连续的索引
+///
+///注册的名称不会被释放，同一个名称总是得到相同的索引
+#[derive(Default)]
+pub struct IdRegistry {
+    names: Vec<&'static str>,
+    indices: HashMap<&'static str, u32>,
+}
+
+impl IdRegistry {
+    pub fn intern(lock: &RwLock<IdRegistry>, name: &str) -> u32 {
+        if let Some(index) = Self::get(lock, name) {
+            return index;
+        }
+
+        let mut registry = lock.write().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(index) = registry.indices.get(name) {
+            return *index;
+        }
+
+        let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+        let index = registry.names.len() as u32;
+        registry.names.push(name);
+        registry.indices.insert(name, index);
+
+        index
+    }
+
+    pub fn get(lock: &RwLock<IdRegistry>, name: &str) -> Option<u32> {
+        let registry = lock.read().unwrap_or_else(PoisonError::into_inner);
+
+        registry.indices.get(name).copied()
+    }
+
+    pub fn name(lock: &RwLock<IdRegistry>, index: u32) -> &'static str {
+        let registry = lock.read().unwrap_or_else(PoisonError::into_inner);
+
+        registry.names[index as usize]
+    }
+
+    pub fn names(lock: &RwLock<IdRegistry>) -> Vec<&'static str> {
+        let registry = lock.read().unwrap_or_else(PoisonError::into_inner);
+
+        registry.names.clone()
+    }
+}
+
+///按照名称查找已经注册的标识符，不会注册新的名称
+///
+///用于只读的查找，避免未知的名称被永久驻留
+pub trait IdLookup<T> {
+    fn lookup(self) -> Option<T>;
+}
+
+///定义一个驻留的标识符类型
+///
+///标识符只包含一个索引，比较和哈希都不需要访问字符串，可以通过名称创建和查找
+macro_rules! define_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(u32);
+
+        impl $name {
+            fn registry() -> &'static std::sync::RwLock<$crate::IdRegistry> {
+                static REGISTRY: std::sync::LazyLock<std::sync::RwLock<$crate::IdRegistry>> =
+                    std::sync::LazyLock::new(Default::default);
+
+                &REGISTRY
+            }
+
+            ///获取名称对应的标识符，名称没有注册时进行注册
+            pub fn new(name: &str) -> Self {
+                Self($crate::IdRegistry::intern(Self::registry(), name))
+            }
+
+            ///获取已经注册的标识符
+            pub fn get(name: &str) -> Option<Self> {
+                $crate::IdRegistry::get(Self::registry(), name).map(Self)
+            }
+
+            pub fn name(&self) -> &'static str {
+                $crate::IdRegistry::name(Self::registry(), self.0)
+            }
+
+            ///所有已经注册的标识符
+            pub fn registered() -> Vec<Self> {
+                (0..$crate::IdRegistry::names(Self::registry()).len() as u32)
+                    .map(Self)
+                    .collect()
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new("")
+            }
+        }
+
+        impl std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_tuple(stringify!($name)).field(&self.name()).finish()
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.name())
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                Self::new(value)
+            }
+        }
+
+        impl From<&String> for $name {
+            fn from(value: &String) -> Self {
+                Self::new(value)
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                Self::new(&value)
+            }
+        }
+
+        impl From<&$name> for $name {
+            fn from(value: &$name) -> Self {
+                *value
+            }
+        }
+
+        impl $crate::IdLookup<$name> for $name {
+            fn lookup(self) -> Option<$name> {
+                Some(self)
+            }
+        }
+
+        impl $crate::IdLookup<$name> for &$name {
+            fn lookup(self) -> Option<$name> {
+                Some(*self)
+            }
+        }
+
+        impl $crate::IdLookup<$name> for &str {
+            fn lookup(self) -> Option<$name> {
+                $name::get(self)
+            }
+        }
+
+        impl $crate::IdLookup<$name> for &String {
+            fn lookup(self) -> Option<$name> {
+                $name::get(self)
+            }
+        }
+
+        impl $crate::IdLookup<$name> for String {
+            fn lookup(self) -> Option<$name> {
+                $name::get(&self)
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.name() == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.name() == *other
+            }
+        }
+
+        ///按照名称序列化，不同进程中同一个名称的索引可能不同
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.name())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                <std::borrow::Cow<'de, str> as serde::Deserialize>::deserialize(deserializer)
+                    .map(|name| Self::new(&name))
+            }
+        }
+    };
+}
+
+pub(crate) use define_id;
+
+#[cfg(test)]
+mod test {
+    use crate::IdLookup;
+
+    define_id!(TestId);
+
+    #[test]
+    fn test_define_id() {
+        assert_eq!(None, TestId::get("test"));
+        assert_eq!(None, IdLookup::<TestId>::lookup("test"));
+        //查找不会注册名称
+        assert!(TestId::registered().is_empty());
+
+        let id = TestId::new("test");
+
+        assert_eq!(id, TestId::new("test"));
+        assert_eq!(Some(id), TestId::get("test"));
+        assert_eq!(Some(id), "test".lookup());
+        assert_ne!(id, TestId::new("other"));
+        assert_eq!("test", id.name());
+        assert_eq!(id, "test");
+        assert!(TestId::registered().contains(&id));
+        assert_eq!("\"test\"", serde_json::to_string(&id).unwrap());
+        assert_eq!(id, serde_json::from_str::<TestId>("\"test\"").unwrap());
+    }
+}
@@ -1,8 +1,15 @@
+mod ai_system;
 mod attribute_system;
+mod battle_system;
+mod id;
 mod number_generator;
+mod serialize;
 mod skill_system;
 
+pub use ai_system::*;
 pub use attribute_system::*;
+pub use battle_system::*;
+pub use id::*;
 pub use number_generator::*;
 pub use skill_system::*;
 
@@ -12,7 +19,8 @@ pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(SkillPlugin);
+        app.add_plugins(SkillPlugin)
+            .add_systems(Update, sync_summons);
     }
 }
 
@@ -0,0 +1,596 @@
+use std::{error::Error, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    DEFAULT_NUMBER_GENERATOR, NumberGeneratorContainer, NumberGeneratorError, NumberRange,
+};
+
+fn default_generator() -> String {
+    DEFAULT_NUMBER_GENERATOR.to_string()
+}
+
+///生成 [0, 1) 之间的小数时使用的精度
+const FLOAT_RESOLUTION: usize = 1 << 24;
+
+///使用指定的随机数生成器生成 [0, 1) 之间的小数
+fn sample_unit(
+    container: &mut NumberGeneratorContainer,
+    generator: &str,
+) -> Result<f32, NumberGeneratorError> {
+    let range = NumberRange::new(0, FLOAT_RESOLUTION).with_name(generator);
+
+    container
+        .generate(&range)
+        .map(|value| value as f32 / FLOAT_RESOLUTION as f32)
+}
+
+///分布的参数错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum DistributionError {
+    ///骰子的最大值超出 i32 的范围
+    DiceOverflow {
+        count: usize,
+        sides: usize,
+        modifier: i32,
+    },
+    ///三角分布的众数不在 [min, max] 之间
+    InvalidTriangular { min: f32, mode: f32, max: f32 },
+}
+
+impl fmt::Display for DistributionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DistributionError::DiceOverflow {
+                count,
+                sides,
+                modifier,
+            } => write!(f, "dice {count}d{sides}{modifier:+} overflows i32"),
+            DistributionError::InvalidTriangular { min, mode, max } => {
+                write!(f, "triangular mode {mode} is not in [{min}, {max}]")
+            }
+        }
+    }
+}
+
+impl Error for DistributionError {}
+
+///骰子，例如 3d6+2 表示投掷 3 个 6 面骰子再加 2
+///
+///在数据中使用骰子表达式描述，例如 { "notation": "3d6+2" }
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "DiceData", into = "DiceData")]
+pub struct Dice {
+    count: usize,
+    sides: usize,
+    modifier: i32,
+    pub generator: String,
+}
+
+impl Dice {
+    ///最大值超出 i32 的范围时返回错误
+    pub fn new(count: usize, sides: usize, modifier: i32) -> Result<Self, DistributionError> {
+        let max = count
+            .checked_mul(sides)
+            .and_then(|value| i32::try_from(value).ok())
+            .and_then(|value| value.checked_add(modifier));
+
+        //最小值不大于最大值，只需要检查最大值
+        if max.is_none() {
+            return Err(DistributionError::DiceOverflow {
+                count,
+                sides,
+                modifier,
+            });
+        }
+
+        Ok(Self {
+            count,
+            sides,
+            modifier,
+            generator: default_generator(),
+        })
+    }
+
+    pub fn with_generator(mut self, generator: &str) -> Self {
+        self.generator = generator.to_string();
+        self
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn sides(&self) -> usize {
+        self.sides
+    }
+
+    pub fn modifier(&self) -> i32 {
+        self.modifier
+    }
+
+    ///Dice::new 保证最大值在 i32 的范围内，所以最小值和最大值不会溢出
+    pub fn min(&self) -> i32 {
+        self.count.min(self.count * self.sides) as i32 + self.modifier
+    }
+
+    pub fn max(&self) -> i32 {
+        (self.count * self.sides) as i32 + self.modifier
+    }
+
+    pub fn roll(
+        &self,
+        container: &mut NumberGeneratorContainer,
+    ) -> Result<i32, NumberGeneratorError> {
+        let mut total = self.modifier;
+
+        if self.sides == 0 {
+            return Ok(total);
+        }
+
+        let range = NumberRange::inclusive(1, self.sides).with_name(&self.generator);
+
+        //生成器返回的值可能超出骰子的面数，使用饱和运算避免溢出
+        for _ in 0..self.count {
+            let value = container.generate(&range)?;
+            total = total.saturating_add(i32::try_from(value).unwrap_or(i32::MAX));
+        }
+
+        Ok(total)
+    }
+}
+
+impl fmt::Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)?;
+
+        if self.modifier != 0 {
+            write!(f, "{:+}", self.modifier)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct DiceData {
+    notation: String,
+    #[serde(default = "default_generator")]
+    generator: String,
+}
+
+impl TryFrom<DiceData> for Dice {
+    type Error = DiceParseError;
+
+    fn try_from(data: DiceData) -> Result<Self, Self::Error> {
+        data.notation
+            .parse::<Dice>()
+            .map(|dice| dice.with_generator(&data.generator))
+    }
+}
+
+impl From<Dice> for DiceData {
+    fn from(dice: Dice) -> Self {
+        Self {
+            notation: dice.to_string(),
+            generator: dice.generator,
+        }
+    }
+}
+
+///骰子表达式解析错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiceParseError {
+    pub notation: String,
+}
+
+impl fmt::Display for DiceParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid dice notation: {}", self.notation)
+    }
+}
+
+impl Error for DiceParseError {}
+
+impl FromStr for Dice {
+    type Err = DiceParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || DiceParseError {
+            notation: s.to_string(),
+        };
+
+        let notation = s.trim().to_lowercase();
+        let (count, rest) = notation.split_once('d').ok_or_else(error)?;
+
+        let count = if count.is_empty() {
+            1
+        } else {
+            count.parse::<usize>().map_err(|_| error())?
+        };
+
+        let (sides, modifier) = match rest.find(['+', '-']) {
+            Some(index) => {
+                let (sides, modifier) = rest.split_at(index);
+                let modifier = modifier.parse::<i32>().map_err(|_| error())?;
+
+                (sides, modifier)
+            }
+            None => (rest, 0),
+        };
+
+        let sides = sides.parse::<usize>().map_err(|_| error())?;
+
+        Dice::new(count, sides, modifier).map_err(|_| error())
+    }
+}
+
+///权重表，按照权重随机选择一项
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeightedTable<T> {
+    entries: Vec<(T, usize)>,
+    #[serde(default = "default_generator")]
+    pub generator: String,
+}
+
+impl<T> Default for WeightedTable<T> {
+    fn default() -> Self {
+        Self {
+            entries: vec![],
+            generator: default_generator(),
+        }
+    }
+}
+
+impl<T> WeightedTable<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_generator(mut self, generator: &str) -> Self {
+        self.generator = generator.to_string();
+        self
+    }
+
+    pub fn with_entry(mut self, value: T, weight: usize) -> Self {
+        self.push(value, weight);
+        self
+    }
+
+    pub fn push(&mut self, value: T, weight: usize) {
+        self.entries.push((value, weight));
+    }
+
+    pub fn entries(&self) -> &[(T, usize)] {
+        &self.entries
+    }
+
+    pub fn total_weight(&self) -> usize {
+        self.entries.iter().map(|(_, weight)| weight).sum()
+    }
+
+    ///随机选择一项，表为空或者总权重为 0 时返回 EmptyRange 错误
+    pub fn pick(
+        &self,
+        container: &mut NumberGeneratorContainer,
+    ) -> Result<&T, NumberGeneratorError> {
+        let range = NumberRange::new(0, self.total_weight()).with_name(&self.generator);
+        let mut value = container.generate(&range)?;
+
+        for (entry, weight) in self.entries.iter() {
+            if value < *weight {
+                return Ok(entry);
+            }
+
+            value -= weight;
+        }
+
+        Err(NumberGeneratorError::EmptyRange(range))
+    }
+}
+
+///小数范围 [start, end)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FloatRange {
+    pub start: f32,
+    pub end: f32,
+    #[serde(default = "default_generator")]
+    pub generator: String,
+}
+
+impl FloatRange {
+    pub fn new(start: f32, end: f32) -> Self {
+        Self {
+            start,
+            end,
+            generator: default_generator(),
+        }
+    }
+
+    pub fn with_generator(mut self, generator: &str) -> Self {
+        self.generator = generator.to_string();
+        self
+    }
+
+    pub fn sample(
+        &self,
+        container: &mut NumberGeneratorContainer,
+    ) -> Result<f32, NumberGeneratorError> {
+        let unit = sample_unit(container, &self.generator)?;
+
+        Ok(self.start + (self.end - self.start) * unit)
+    }
+}
+
+///数字分布，用于在数据中描述随机数
+///
+///在数据中使用 type 区分分布，参数在 value 中，例如 { "type": "constant", "value": 5 }
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum NumberDistribution {
+    Constant(f32),
+    Range(NumberRange),
+    Dice(Dice),
+    Weighted(WeightedTable<f32>),
+    Float(FloatRange),
+    Normal {
+        mean: f32,
+        std_dev: f32,
+        #[serde(default = "default_generator")]
+        generator: String,
+    },
+    Triangular {
+        min: f32,
+        mode: f32,
+        max: f32,
+        #[serde(default = "default_generator")]
+        generator: String,
+    },
+}
+
+impl NumberDistribution {
+    pub fn normal(mean: f32, std_dev: f32) -> Self {
+        NumberDistribution::Normal {
+            mean,
+            std_dev,
+            generator: default_generator(),
+        }
+    }
+
+    ///众数不在 [min, max] 之间时返回错误
+    pub fn triangular(min: f32, mode: f32, max: f32) -> Result<Self, DistributionError> {
+        if !(min <= mode && mode <= max) {
+            return Err(DistributionError::InvalidTriangular { min, mode, max });
+        }
+
+        Ok(NumberDistribution::Triangular {
+            min,
+            mode,
+            max,
+            generator: default_generator(),
+        })
+    }
+
+    pub fn sample(
+        &self,
+        container: &mut NumberGeneratorContainer,
+    ) -> Result<f32, NumberGeneratorError> {
+        match self {
+            NumberDistribution::Constant(value) => Ok(*value),
+            NumberDistribution::Range(range) => container.generate(range).map(|v| v as f32),
+            NumberDistribution::Dice(dice) => dice.roll(container).map(|v| v as f32),
+            NumberDistribution::Weighted(table) => table.pick(container).copied(),
+            NumberDistribution::Float(range) => range.sample(container),
+            NumberDistribution::Normal {
+                mean,
+                std_dev,
+                generator,
+            } => {
+                //Box-Muller 变换
+                let u1 = 1.0 - sample_unit(container, generator)?;
+                let u2 = sample_unit(container, generator)?;
+
+                let z = (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos();
+
+                Ok(mean + std_dev * z)
+            }
+            NumberDistribution::Triangular {
+                min,
+                mode,
+                max,
+                generator,
+            } => {
+                let u = sample_unit(container, generator)?;
+                let width = max - min;
+
+                if width <= 0.0 {
+                    return Ok(*min);
+                }
+
+                //直接构造的分布可能没有经过检查
+                let mode = mode.clamp(*min, *max);
+                let split = (mode - min) / width;
+
+                let value = if u < split {
+                    min + (u * width * (mode - min)).sqrt()
+                } else {
+                    max - ((1.0 - u) * width * (max - mode)).sqrt()
+                };
+
+                Ok(value)
+            }
+        }
+    }
+}
+
+impl From<Dice> for NumberDistribution {
+    fn from(value: Dice) -> Self {
+        NumberDistribution::Dice(value)
+    }
+}
+
+impl From<NumberRange> for NumberDistribution {
+    fn from(value: NumberRange) -> Self {
+        NumberDistribution::Range(value)
+    }
+}
+
+impl From<FloatRange> for NumberDistribution {
+    fn from(value: FloatRange) -> Self {
+        NumberDistribution::Float(value)
+    }
+}
+
+impl From<f32> for NumberDistribution {
+    fn from(value: f32) -> Self {
+        NumberDistribution::Constant(value)
+    }
+}
+
+///解析常量或者骰子表达式，例如 "5" 或者 "3d6+2"
+impl FromStr for NumberDistribution {
+    type Err = DiceParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().parse::<f32>() {
+            Ok(value) => Ok(NumberDistribution::Constant(value)),
+            Err(_) => s.parse::<Dice>().map(NumberDistribution::Dice),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        Dice, DistributionError, FloatRange, NumberDistribution, NumberGenerator,
+        NumberGeneratorContainer, NumberGeneratorError, NumberRange, WeightedTable,
+    };
+
+    ///总是返回范围内最小值的生成器
+    struct MinNumberGenerator;
+
+    impl NumberGenerator for MinNumberGenerator {
+        fn generate(&mut self, range: &NumberRange) -> Result<usize, NumberGeneratorError> {
+            Ok(range.start())
+        }
+    }
+
+    #[test]
+    fn test_dice() {
+        let dice: Dice = "3d6+2".parse().unwrap();
+        assert_eq!(Dice::new(3, 6, 2).unwrap(), dice);
+        assert_eq!(5, dice.min());
+        assert_eq!(20, dice.max());
+        assert_eq!((3, 6, 2), (dice.count(), dice.sides(), dice.modifier()));
+        assert_eq!("3d6+2", dice.to_string());
+
+        assert_eq!(Dice::new(1, 20, 0), Ok("d20".parse().unwrap()));
+        assert_eq!(Dice::new(2, 8, -1), Ok("2D8-1".parse().unwrap()));
+        assert!("3x6".parse::<Dice>().is_err());
+        assert!("3d".parse::<Dice>().is_err());
+        assert!("100000d100000".parse::<Dice>().is_err());
+        assert!(matches!(
+            Dice::new(2, usize::MAX, 0),
+            Err(DistributionError::DiceOverflow { .. })
+        ));
+        assert!(Dice::new(1, 6, i32::MAX).is_err());
+
+        let mut container = NumberGeneratorContainer::empty();
+        container.register("min", MinNumberGenerator);
+
+        assert_eq!(Ok(5), dice.with_generator("min").roll(&mut container));
+    }
+
+    #[test]
+    fn test_number_distribution() {
+        let mut container = NumberGeneratorContainer::new();
+
+        let table = WeightedTable::new()
+            .with_entry(1.0, 0)
+            .with_entry(2.0, 10)
+            .with_entry(3.0, 0);
+
+        for _ in 0..20 {
+            assert_eq!(Ok(&2.0), table.pick(&mut container));
+
+            let value = FloatRange::new(1.0, 2.0).sample(&mut container).unwrap();
+            assert!((1.0..2.0).contains(&value));
+
+            let value = NumberDistribution::triangular(0.0, 1.0, 4.0)
+                .unwrap()
+                .sample(&mut container)
+                .unwrap();
+            assert!((0.0..=4.0).contains(&value));
+
+            let value = "2d4".parse::<NumberDistribution>().unwrap();
+            let value = container.sample(&value).unwrap();
+            assert!((2.0..=8.0).contains(&value));
+        }
+
+        assert_eq!(
+            Err(DistributionError::InvalidTriangular {
+                min: 0.0,
+                mode: 5.0,
+                max: 4.0
+            }),
+            NumberDistribution::triangular(0.0, 5.0, 4.0)
+        );
+        assert!(NumberDistribution::triangular(0.0, f32::NAN, 4.0).is_err());
+
+        assert!(
+            NumberDistribution::normal(10.0, 0.0)
+                .sample(&mut container)
+                .is_ok_and(|v| v == 10.0)
+        );
+        assert_eq!(
+            Ok(4.0),
+            container.sample(&NumberDistribution::Constant(4.0))
+        );
+        assert_eq!(
+            Ok(3.0),
+            container.sample(&NumberRange::inclusive(3, 3).into())
+        );
+    }
+
+    #[test]
+    fn test_number_distribution_serde() {
+        let distributions = vec![
+            NumberDistribution::Constant(5.0),
+            NumberRange::inclusive(1, 6).with_name("prd").into(),
+            Dice::new(3, 6, 2).unwrap().with_generator("seeded").into(),
+            NumberDistribution::Weighted(
+                WeightedTable::new().with_entry(1.0, 3).with_entry(2.0, 1),
+            ),
+            FloatRange::new(0.5, 1.5).into(),
+            NumberDistribution::normal(10.0, 2.0),
+            NumberDistribution::triangular(0.0, 1.0, 4.0).unwrap(),
+        ];
+
+        let json = serde_json::to_string(&distributions).unwrap();
+        assert_eq!(
+            distributions,
+            serde_json::from_str::<Vec<NumberDistribution>>(&json).unwrap()
+        );
+
+        //生成器名称可以省略
+        let json = r#"[
+            { "type": "dice", "value": { "notation": "2d4-1" } },
+            { "type": "weighted", "value": { "entries": [[1.0, 3], [2.0, 1]] } },
+            { "type": "normal", "value": { "mean": 10.0, "std_dev": 2.0 } }
+        ]"#;
+        assert_eq!(
+            vec![
+                Dice::new(2, 4, -1).unwrap().into(),
+                NumberDistribution::Weighted(
+                    WeightedTable::new().with_entry(1.0, 3).with_entry(2.0, 1)
+                ),
+                NumberDistribution::normal(10.0, 2.0),
+            ],
+            serde_json::from_str::<Vec<NumberDistribution>>(json).unwrap()
+        );
+
+        //骰子在反序列化时同样检查溢出
+        let json = r#"{ "type": "dice", "value": { "notation": "100000d100000" } }"#;
+        assert!(serde_json::from_str::<NumberDistribution>(json).is_err());
+    }
+}
@@ -0,0 +1,389 @@
+mod distribution;
+mod prd;
+mod recording;
+mod scripted;
+mod seeded;
+mod state;
+
+pub use distribution::*;
+pub use prd::*;
+pub use recording::*;
+pub use scripted::*;
+pub use seeded::*;
+pub use state::*;
+
+use std::{error::Error, fmt};
+
+use bevy::platform::collections::HashMap;
+use rand::{Rng, rngs::ThreadRng};
+use serde::{Deserialize, Serialize};
+
+///默认的随机数生成器名称
+pub const DEFAULT_NUMBER_GENERATOR: &str = "rand";
+
+///伪随机分布生成器的名称
+pub const PRD_NUMBER_GENERATOR: &str = "prd";
+
+///概率判定时使用的精度
+pub const CHANCE_RESOLUTION: usize = 1_000_000;
+
+pub struct RandNumberGenerator(ThreadRng);
+
+impl Default for RandNumberGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RandNumberGenerator {
+    pub fn new() -> Self {
+        Self(rand::rng())
+    }
+}
+
+impl NumberGenerator for RandNumberGenerator {
+    fn generate(&mut self, range: &NumberRange) -> Result<usize, NumberGeneratorError> {
+        if range.is_empty() {
+            return Err(NumberGeneratorError::EmptyRange(range.clone()));
+        }
+
+        Ok(self.0.random_range(range.start..range.end))
+    }
+}
+
+///随机数生成器错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumberGeneratorError {
+    ///没有注册对应名称的生成器，且没有设置备用生成器
+    UnknownGenerator(String),
+    ///范围中没有任何数字
+    EmptyRange(NumberRange),
+    ///生成器自身报告的错误
+    Failure(String),
+}
+
+impl fmt::Display for NumberGeneratorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NumberGeneratorError::UnknownGenerator(name) => {
+                write!(f, "unknown number generator: {name}")
+            }
+            NumberGeneratorError::EmptyRange(range) => write!(
+                f,
+                "empty range {}..{} for number generator {}",
+                range.start, range.end, range.name
+            ),
+            NumberGeneratorError::Failure(message) => {
+                write!(f, "number generator failure: {message}")
+            }
+        }
+    }
+}
+
+impl Error for NumberGeneratorError {}
+
+pub struct NumberGeneratorContainer {
+    generators: HashMap<String, Box<dyn NumberGenerator>>,
+    fallback: Option<Box<dyn NumberGenerator>>,
+    recorder: Option<NumberRecorder>,
+}
+
+impl Default for NumberGeneratorContainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NumberGeneratorContainer {
+    pub fn empty() -> Self {
+        NumberGeneratorContainer {
+            generators: Default::default(),
+            fallback: None,
+            recorder: None,
+        }
+    }
+
+    pub fn new() -> Self {
+        let mut empty = Self::empty();
+        empty.register(DEFAULT_NUMBER_GENERATOR, RandNumberGenerator::new());
+        empty.register(PRD_NUMBER_GENERATOR, PrdNumberGenerator::new());
+
+        empty
+    }
+
+    ///使用固定种子创建默认的生成器，相同的种子总是得到相同的结果
+    pub fn seeded(seed: u64) -> Self {
+        let mut empty = Self::empty();
+        empty.register(DEFAULT_NUMBER_GENERATOR, SeededNumberGenerator::new(seed));
+        empty.register(
+            PRD_NUMBER_GENERATOR,
+            PrdNumberGenerator::from_generator(SeededNumberGenerator::new(
+                seed ^ 0xA5A5_A5A5_A5A5_A5A5,
+            )),
+        );
+
+        empty
+    }
+
+    pub fn register<T: NumberGenerator>(&mut self, name: &str, value: T) {
+        self.generators.insert(name.to_string(), Box::new(value));
+    }
+
+    ///设置备用生成器，名称没有注册时使用备用生成器
+    pub fn set_fallback<T: NumberGenerator>(&mut self, value: T) {
+        self.fallback = Some(Box::new(value));
+    }
+
+    pub fn clear_fallback(&mut self) {
+        self.fallback = None;
+    }
+
+    pub fn has_fallback(&self) -> bool {
+        self.fallback.is_some()
+    }
+
+    ///记录之后所有生成的数字和判定，例如用于战斗日志
+    pub fn set_recorder(&mut self, recorder: NumberRecorder) {
+        self.recorder = Some(recorder);
+    }
+
+    pub fn clear_recorder(&mut self) {
+        self.recorder = None;
+    }
+
+    pub fn recorder(&self) -> Option<&NumberRecorder> {
+        self.recorder.as_ref()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.generators.contains_key(name)
+    }
+
+    ///所有注册的生成器名称，可以用于校验技能数据
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.generators.keys().map(|name| name.as_str())
+    }
+
+    fn get_generator(
+        &mut self,
+        name: &str,
+    ) -> Result<&mut Box<dyn NumberGenerator>, NumberGeneratorError> {
+        match self.generators.get_mut(name) {
+            Some(generator) => Ok(generator),
+            None => self
+                .fallback
+                .as_mut()
+                .ok_or_else(|| NumberGeneratorError::UnknownGenerator(name.to_string())),
+        }
+    }
+
+    pub fn generate(&mut self, range: &NumberRange) -> Result<usize, NumberGeneratorError> {
+        let generator = self.get_generator(&range.name)?;
+
+        if range.is_empty() {
+            return Err(NumberGeneratorError::EmptyRange(range.clone()));
+        }
+
+        let value = generator.generate(range)?;
+
+        if let Some(recorder) = self.recorder.as_ref() {
+            recorder.push(NumberRecord::Generate {
+                range: range.clone(),
+                value,
+            });
+        }
+
+        Ok(value)
+    }
+
+    ///按照概率进行判定，使用的生成器为 chance.name
+    pub fn roll(&mut self, chance: &Chance) -> Result<bool, NumberGeneratorError> {
+        let value = self.get_generator(&chance.name)?.roll(chance)?;
+
+        if let Some(recorder) = self.recorder.as_ref() {
+            recorder.push(NumberRecord::Roll {
+                chance: chance.clone(),
+                value,
+            });
+        }
+
+        Ok(value)
+    }
+
+    ///按照分布生成数字
+    pub fn sample(
+        &mut self,
+        distribution: &NumberDistribution,
+    ) -> Result<f32, NumberGeneratorError> {
+        distribution.sample(self)
+    }
+}
+
+pub trait NumberGenerator: 'static {
+    ///生成 range 中的数字，生成器无法生成时返回错误
+    fn generate(&mut self, range: &NumberRange) -> Result<usize, NumberGeneratorError>;
+
+    ///按照概率进行判定，成功时返回 true
+    ///
+    ///默认在 [0, CHANCE_RESOLUTION) 中均匀生成数字，有状态的生成器可以根据 chance.key 调整概率
+    fn roll(&mut self, chance: &Chance) -> Result<bool, NumberGeneratorError> {
+        let range = NumberRange::new(0, CHANCE_RESOLUTION).with_name(&chance.name);
+
+        Ok((self.generate(&range)? as f32) < chance.probability * CHANCE_RESOLUTION as f32)
+    }
+
+    ///保存生成器的状态，无法保存时返回 None
+    fn state(&self) -> Option<NumberGeneratorState> {
+        None
+    }
+}
+
+impl NumberGenerator for Box<dyn NumberGenerator> {
+    fn generate(&mut self, range: &NumberRange) -> Result<usize, NumberGeneratorError> {
+        self.as_mut().generate(range)
+    }
+
+    fn roll(&mut self, chance: &Chance) -> Result<bool, NumberGeneratorError> {
+        self.as_mut().roll(chance)
+    }
+
+    fn state(&self) -> Option<NumberGeneratorState> {
+        self.as_ref().state()
+    }
+}
+
+///概率判定
+///
+///probability 为 [0, 1] 之间的概率，key 用于区分判定的来源，例如施法者和触发效果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chance {
+    pub probability: f32,
+    pub key: Option<String>,
+    pub name: String,
+}
+
+impl Chance {
+    pub fn new(probability: f32) -> Self {
+        Self {
+            probability,
+            key: None,
+            name: DEFAULT_NUMBER_GENERATOR.to_string(),
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn with_key(mut self, key: &str) -> Self {
+        self.key = Some(key.to_string());
+        self
+    }
+
+    ///使用施法者和触发效果组成判定的 key
+    pub fn with_owner_key(self, owner: impl std::fmt::Display, proc: &str) -> Self {
+        self.with_key(&format!("{owner}/{proc}"))
+    }
+}
+
+///整数范围 [start, end)，name 为使用的随机数生成器名称
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NumberRange {
+    start: usize,
+    end: usize,
+    pub name: String,
+}
+
+impl NumberRange {
+    ///创建范围 [start, end)
+    pub fn new(start: usize, end: usize) -> Self {
+        let mut range = Self {
+            start,
+            end: start,
+            name: DEFAULT_NUMBER_GENERATOR.to_string(),
+        };
+        range.set_end(end);
+
+        range
+    }
+
+    ///创建范围 [start, end]
+    pub fn inclusive(start: usize, end: usize) -> Self {
+        Self::new(start.min(end), start.max(end) + 1)
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn set_end(&mut self, end: usize) {
+        if end < self.start {
+            self.start = end;
+        }
+
+        self.end = end;
+    }
+
+    pub fn set_start(&mut self, start: usize) {
+        if start > self.end {
+            self.end = start;
+        }
+
+        self.start = start;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        NumberGeneratorContainer, NumberGeneratorError, NumberRange, ScriptedNumberGenerator,
+    };
+
+    #[test]
+    fn test_number_generator_container() {
+        let mut container = NumberGeneratorContainer::new();
+
+        let range = NumberRange::new(0, 10).with_name("unknown");
+        assert_eq!(
+            Err(NumberGeneratorError::UnknownGenerator(
+                "unknown".to_string()
+            )),
+            container.generate(&range)
+        );
+
+        let empty_range = NumberRange::new(5, 5);
+        assert_eq!(
+            Err(NumberGeneratorError::EmptyRange(empty_range.clone())),
+            container.generate(&empty_range)
+        );
+
+        container.set_fallback(ScriptedNumberGenerator::new([3]));
+        assert_eq!(Ok(3), container.generate(&range));
+        assert!(matches!(
+            container.generate(&range),
+            Err(NumberGeneratorError::Failure(_))
+        ));
+
+        let mut names = container.names().collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(vec!["prd", "rand"], names);
+    }
+}
@@ -0,0 +1,221 @@
+use bevy::platform::collections::HashMap;
+
+use super::{
+    CHANCE_RESOLUTION, Chance, NumberGenerator, NumberGeneratorError, NumberGeneratorState,
+    NumberRange, RandNumberGenerator,
+};
+
+///根据期望概率计算伪随机分布的系数 C
+///
+///第 n 次判定的成功概率为 min(C * n, 1)，成功后 n 重置为 1
+pub fn prd_constant(probability: f32) -> f32 {
+    let probability = probability.clamp(0.0, 1.0) as f64;
+
+    if probability <= 0.0 || probability >= 1.0 {
+        return probability as f32;
+    }
+
+    let mut upper = probability;
+    let mut lower = 0.0;
+    let mut constant = probability;
+    let mut previous = 1.0;
+
+    loop {
+        let middle = (upper + lower) / 2.0;
+        let value = prd_probability(middle);
+
+        if (value - previous).abs() <= f64::EPSILON {
+            break;
+        }
+
+        if value > probability {
+            upper = middle;
+        } else {
+            lower = middle;
+        }
+
+        previous = value;
+        constant = middle;
+    }
+
+    constant as f32
+}
+
+///根据系数 C 计算伪随机分布的平均概率
+fn prd_probability(constant: f64) -> f64 {
+    let max_fails = (1.0 / constant).ceil() as usize;
+
+    let mut probability_by_n = 0.0;
+    let mut sum = 0.0;
+
+    for n in 1..=max_fails {
+        let probability_on_n = (n as f64 * constant).min(1.0) * (1.0 - probability_by_n);
+        probability_by_n += probability_on_n;
+        sum += n as f64 * probability_on_n;
+    }
+
+    1.0 / sum
+}
+
+///伪随机分布生成器
+///
+///每次判定失败后成功的概率都会增加，成功后重置，整体的成功概率与 Chance::probability 一致
+///判定的状态按照 Chance::key 分别记录，没有 key 的判定与普通的随机判定一致
+pub struct PrdNumberGenerator<T: NumberGenerator = RandNumberGenerator> {
+    generator: T,
+    failures: HashMap<String, u32>,
+    constants: HashMap<u32, f32>,
+}
+
+impl Default for PrdNumberGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrdNumberGenerator {
+    pub fn new() -> Self {
+        Self::from_generator(RandNumberGenerator::new())
+    }
+}
+
+impl<T: NumberGenerator> PrdNumberGenerator<T> {
+    ///使用 generator 作为均匀分布的来源
+    pub fn from_generator(generator: T) -> Self {
+        Self {
+            generator,
+            failures: Default::default(),
+            constants: Default::default(),
+        }
+    }
+
+    ///key 对应的判定连续失败的次数
+    pub fn failures(&self, key: &str) -> u32 {
+        self.failures.get(key).copied().unwrap_or_default()
+    }
+
+    pub fn set_failures(&mut self, key: &str, failures: u32) {
+        if failures == 0 {
+            self.failures.remove(key);
+        } else {
+            self.failures.insert(key.to_string(), failures);
+        }
+    }
+
+    pub fn reset(&mut self, key: &str) {
+        self.failures.remove(key);
+    }
+
+    pub fn clear(&mut self) {
+        self.failures.clear();
+    }
+
+    fn get_constant(&mut self, probability: f32) -> f32 {
+        *self
+            .constants
+            .entry(probability.to_bits())
+            .or_insert_with(|| prd_constant(probability))
+    }
+}
+
+impl<T: NumberGenerator> NumberGenerator for PrdNumberGenerator<T> {
+    fn generate(&mut self, range: &NumberRange) -> Result<usize, NumberGeneratorError> {
+        self.generator.generate(range)
+    }
+
+    fn roll(&mut self, chance: &Chance) -> Result<bool, NumberGeneratorError> {
+        let Some(key) = chance.key.as_ref() else {
+            return self.generator.roll(chance);
+        };
+
+        let failures = self.failures(key);
+        let constant = self.get_constant(chance.probability);
+        let probability = (constant * (failures + 1) as f32).min(1.0);
+
+        let range = NumberRange::new(0, CHANCE_RESOLUTION).with_name(&chance.name);
+        let success =
+            (self.generator.generate(&range)? as f32) < probability * CHANCE_RESOLUTION as f32;
+
+        if success {
+            self.failures.remove(key);
+        } else {
+            self.failures.insert(key.clone(), failures + 1);
+        }
+
+        Ok(success)
+    }
+
+    fn state(&self) -> Option<NumberGeneratorState> {
+        Some(NumberGeneratorState::Prd {
+            generator: Box::new(self.generator.state()?),
+            failures: self
+                .failures
+                .iter()
+                .map(|(key, failures)| (key.clone(), *failures))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        CHANCE_RESOLUTION, Chance, NumberGenerator, PrdNumberGenerator, ScriptedNumberGenerator,
+        SeededNumberGenerator, prd_constant,
+    };
+
+    #[test]
+    fn test_prd_number_generator() {
+        let constant = prd_constant(0.25);
+        assert!((constant - 0.0847).abs() < 0.001);
+
+        let chance = Chance::new(0.25).with_owner_key(1, "crit");
+
+        //每次都取中间值，第 n 次判定的概率为 C * n，超过 0.5 时成功
+        let middle = CHANCE_RESOLUTION / 2;
+        let mut generator =
+            PrdNumberGenerator::from_generator(ScriptedNumberGenerator::new([middle; 12]));
+
+        for failures in 1..=5 {
+            assert_eq!(Ok(false), generator.roll(&chance));
+            assert_eq!(failures, generator.failures("1/crit"));
+        }
+        assert!(constant * 6.0 > 0.5);
+        assert_eq!(Ok(true), generator.roll(&chance));
+        assert_eq!(0, generator.failures("1/crit"));
+
+        //不同的 key 分别记录
+        assert_eq!(
+            Ok(false),
+            generator.roll(&chance.clone().with_owner_key(2, "crit"))
+        );
+        assert_eq!(1, generator.failures("2/crit"));
+        assert_eq!(0, generator.failures("1/crit"));
+
+        //连续失败之后概率达到 1，一定成功
+        let max_failures = (1.0 / constant).ceil() as u32;
+        let mut generator = PrdNumberGenerator::from_generator(ScriptedNumberGenerator::new(vec![
+                CHANCE_RESOLUTION
+                    - 1;
+                max_failures
+                    as usize
+            ]));
+
+        for _ in 1..max_failures {
+            assert_eq!(Ok(false), generator.roll(&chance));
+        }
+        assert_eq!(max_failures - 1, generator.failures("1/crit"));
+        assert_eq!(Ok(true), generator.roll(&chance));
+
+        generator.set_failures("1/crit", 3);
+        generator.reset("1/crit");
+        assert_eq!(0, generator.failures("1/crit"));
+
+        //使用固定种子时整体的成功概率与期望概率一致
+        let mut generator = PrdNumberGenerator::from_generator(SeededNumberGenerator::new(7));
+        let success_count = (0..20000)
+            .filter(|_| generator.roll(&chance).unwrap())
+            .count();
+        assert!((0.24..0.26).contains(&(success_count as f32 / 20000.0)));
+    }
+}
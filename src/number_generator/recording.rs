@@ -0,0 +1,142 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Chance, NumberGenerator, NumberGeneratorError, NumberGeneratorState, NumberRange};
+
+///生成器的一次调用记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NumberRecord {
+    Generate { range: NumberRange, value: usize },
+    Roll { chance: Chance, value: bool },
+}
+
+///记录的句柄，生成器注册到容器之后仍然可以通过句柄读取记录
+#[derive(Debug, Clone, Default)]
+pub struct NumberRecorder(Arc<Mutex<Vec<NumberRecord>>>);
+
+impl NumberRecorder {
+    pub fn records(&self) -> Vec<NumberRecord> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///取出所有记录并清空
+    pub fn take(&self) -> Vec<NumberRecord> {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    pub fn clear(&self) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    pub(crate) fn push(&self, record: NumberRecord) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(record);
+    }
+}
+
+///记录另一个生成器的所有调用和结果
+pub struct RecordingNumberGenerator<T: NumberGenerator> {
+    generator: T,
+    recorder: NumberRecorder,
+}
+
+impl<T: NumberGenerator> RecordingNumberGenerator<T> {
+    pub fn new(generator: T) -> Self {
+        Self {
+            generator,
+            recorder: NumberRecorder::default(),
+        }
+    }
+
+    pub fn recorder(&self) -> NumberRecorder {
+        self.recorder.clone()
+    }
+}
+
+impl<T: NumberGenerator> NumberGenerator for RecordingNumberGenerator<T> {
+    fn generate(&mut self, range: &NumberRange) -> Result<usize, NumberGeneratorError> {
+        let value = self.generator.generate(range)?;
+
+        self.recorder.push(NumberRecord::Generate {
+            range: range.clone(),
+            value,
+        });
+
+        Ok(value)
+    }
+
+    fn roll(&mut self, chance: &Chance) -> Result<bool, NumberGeneratorError> {
+        let value = self.generator.roll(chance)?;
+
+        self.recorder.push(NumberRecord::Roll {
+            chance: chance.clone(),
+            value,
+        });
+
+        Ok(value)
+    }
+
+    ///只保存被记录的生成器的状态，恢复后不会继续记录
+    fn state(&self) -> Option<NumberGeneratorState> {
+        self.generator.state()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        Chance, NumberGeneratorContainer, NumberRange, RandNumberGenerator,
+        RecordingNumberGenerator, ScriptedNumberGenerator,
+    };
+
+    #[test]
+    fn test_recording_number_generator() {
+        let generator = RecordingNumberGenerator::new(RandNumberGenerator::new());
+        let recorder = generator.recorder();
+
+        let mut container = NumberGeneratorContainer::empty();
+        container.register("rand", generator);
+
+        let range = NumberRange::new(0, 100);
+        let chance = Chance::new(0.5);
+
+        let values = (
+            container.generate(&range),
+            container.roll(&chance),
+            container.generate(&range),
+        );
+
+        assert_eq!(3, recorder.len());
+
+        container.register(
+            "rand",
+            ScriptedNumberGenerator::from_records(&recorder.records()),
+        );
+
+        let replayed = (
+            container.generate(&range),
+            container.roll(&chance),
+            container.generate(&range),
+        );
+
+        assert_eq!(values, replayed);
+    }
+}
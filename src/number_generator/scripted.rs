@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Chance, NumberGenerator, NumberGeneratorError, NumberGeneratorState, NumberRange, NumberRecord,
+};
+
+///脚本中的一个值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ScriptedValue {
+    Number(usize),
+    Roll(bool),
+}
+
+///按照脚本依次返回数字的生成器，用于测试和回放
+///
+///脚本用尽、数字不在范围内或者类型不匹配时返回 Failure 错误
+#[derive(Debug, Default)]
+pub struct ScriptedNumberGenerator {
+    values: VecDeque<ScriptedValue>,
+}
+
+impl ScriptedNumberGenerator {
+    pub fn new(values: impl IntoIterator<Item = usize>) -> Self {
+        Self {
+            values: values.into_iter().map(ScriptedValue::Number).collect(),
+        }
+    }
+
+    ///根据记录创建生成器，按照记录的顺序返回结果
+    pub fn from_records<'a>(records: impl IntoIterator<Item = &'a NumberRecord>) -> Self {
+        Self {
+            values: records
+                .into_iter()
+                .map(|record| match record {
+                    NumberRecord::Generate { value, .. } => ScriptedValue::Number(*value),
+                    NumberRecord::Roll { value, .. } => ScriptedValue::Roll(*value),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn then(mut self, value: usize) -> Self {
+        self.push(ScriptedValue::Number(value));
+        self
+    }
+
+    pub fn then_roll(mut self, value: bool) -> Self {
+        self.push(ScriptedValue::Roll(value));
+        self
+    }
+
+    pub fn push(&mut self, value: ScriptedValue) {
+        self.values.push_back(value);
+    }
+
+    ///剩余的脚本值
+    pub fn remaining(&self) -> usize {
+        self.values.len()
+    }
+
+    fn next(&mut self) -> Result<ScriptedValue, NumberGeneratorError> {
+        self.values.pop_front().ok_or_else(|| {
+            NumberGeneratorError::Failure("scripted number generator is exhausted".to_string())
+        })
+    }
+}
+
+impl NumberGenerator for ScriptedNumberGenerator {
+    fn generate(&mut self, range: &NumberRange) -> Result<usize, NumberGeneratorError> {
+        match self.next()? {
+            ScriptedValue::Number(value) if value >= range.start() && value < range.end() => {
+                Ok(value)
+            }
+            ScriptedValue::Number(value) => Err(NumberGeneratorError::Failure(format!(
+                "scripted value {value} is out of range {}..{}",
+                range.start(),
+                range.end()
+            ))),
+            ScriptedValue::Roll(value) => Err(NumberGeneratorError::Failure(format!(
+                "scripted value is a roll ({value}) but a number in {range:?} was requested"
+            ))),
+        }
+    }
+
+    fn roll(&mut self, chance: &Chance) -> Result<bool, NumberGeneratorError> {
+        match self.next()? {
+            ScriptedValue::Roll(value) => Ok(value),
+            ScriptedValue::Number(value) => Err(NumberGeneratorError::Failure(format!(
+                "scripted value is a number ({value}) but a roll for {chance:?} was requested"
+            ))),
+        }
+    }
+
+    fn state(&self) -> Option<NumberGeneratorState> {
+        Some(NumberGeneratorState::Scripted {
+            values: self.values.iter().cloned().collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{NumberGenerator, NumberGeneratorError, NumberRange, ScriptedNumberGenerator};
+
+    #[test]
+    fn test_scripted_number_generator() {
+        let mut generator = ScriptedNumberGenerator::new([3, 10]);
+
+        assert_eq!(Ok(3), generator.generate(&NumberRange::new(0, 10)));
+        assert_eq!(1, generator.remaining());
+
+        assert!(matches!(
+            generator.generate(&NumberRange::new(0, 10)),
+            Err(NumberGeneratorError::Failure(message)) if message.contains("out of range")
+        ));
+        assert!(matches!(
+            generator.generate(&NumberRange::new(0, 10)),
+            Err(NumberGeneratorError::Failure(message)) if message.contains("exhausted")
+        ));
+    }
+}
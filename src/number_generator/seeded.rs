@@ -0,0 +1,72 @@
+use super::{NumberGenerator, NumberGeneratorError, NumberGeneratorState, NumberRange};
+
+///使用固定种子的生成器，相同的种子总是生成相同的数字序列
+///
+///使用 SplitMix64 算法，状态只有一个 u64，可以直接保存和恢复
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeededNumberGenerator {
+    state: u64,
+}
+
+impl SeededNumberGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    ///当前的状态，使用 SeededNumberGenerator::new(state) 可以从这里继续生成
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl NumberGenerator for SeededNumberGenerator {
+    fn generate(&mut self, range: &NumberRange) -> Result<usize, NumberGeneratorError> {
+        if range.is_empty() {
+            return Err(NumberGeneratorError::EmptyRange(range.clone()));
+        }
+
+        let offset = (self.next_u64() as u128 * range.len() as u128) >> 64;
+
+        Ok(range.start() + offset as usize)
+    }
+
+    fn state(&self) -> Option<NumberGeneratorState> {
+        Some(NumberGeneratorState::Seeded { state: self.state })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{NumberGenerator, NumberRange, SeededNumberGenerator};
+
+    #[test]
+    fn test_seeded_number_generator() {
+        let range = NumberRange::new(10, 20);
+
+        let mut a = SeededNumberGenerator::new(42);
+        let mut b = SeededNumberGenerator::new(42);
+
+        for _ in 0..100 {
+            let value = a.generate(&range).unwrap();
+            assert!((10..20).contains(&value));
+            assert_eq!(value, b.generate(&range).unwrap());
+        }
+
+        let mut c = SeededNumberGenerator::new(a.state());
+        assert_eq!(a.generate(&range), c.generate(&range));
+        assert!(
+            SeededNumberGenerator::new(1)
+                .generate(&NumberRange::new(3, 3))
+                .is_err()
+        );
+    }
+}
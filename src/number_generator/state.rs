@@ -0,0 +1,155 @@
+use std::{
+    collections::BTreeMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{NumberGenerator, PrdNumberGenerator, ScriptedNumberGenerator, ScriptedValue};
+use super::{NumberGeneratorContainer, NumberGeneratorError, SeededNumberGenerator};
+
+///生成器保存的状态，可以恢复为完全相同的生成器
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NumberGeneratorState {
+    Seeded {
+        state: u64,
+    },
+    Prd {
+        generator: Box<NumberGeneratorState>,
+        failures: BTreeMap<String, u32>,
+    },
+    Scripted {
+        values: Vec<ScriptedValue>,
+    },
+}
+
+impl NumberGeneratorState {
+    ///根据状态创建生成器
+    pub fn to_generator(&self) -> Box<dyn NumberGenerator> {
+        match self {
+            NumberGeneratorState::Seeded { state } => Box::new(SeededNumberGenerator::new(*state)),
+            NumberGeneratorState::Prd {
+                generator,
+                failures,
+            } => {
+                let mut generator = PrdNumberGenerator::from_generator(generator.to_generator());
+
+                for (key, failures) in failures.iter() {
+                    generator.set_failures(key, *failures);
+                }
+
+                Box::new(generator)
+            }
+            NumberGeneratorState::Scripted { values } => {
+                let mut generator = ScriptedNumberGenerator::default();
+
+                for value in values.iter() {
+                    generator.push(value.clone());
+                }
+
+                Box::new(generator)
+            }
+        }
+    }
+}
+
+///容器中所有生成器的状态
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NumberGeneratorContainerState {
+    pub generators: BTreeMap<String, NumberGeneratorState>,
+    #[serde(default)]
+    pub fallback: Option<NumberGeneratorState>,
+}
+
+impl NumberGeneratorContainer {
+    ///保存所有生成器的状态，有生成器无法保存时返回错误，例如使用系统随机数的生成器
+    pub fn save_state(&self) -> Result<NumberGeneratorContainerState, NumberGeneratorError> {
+        let save = |name: &str, generator: &dyn NumberGenerator| {
+            generator.state().ok_or_else(|| {
+                NumberGeneratorError::Failure(format!("number generator {name} can not be saved"))
+            })
+        };
+
+        let mut state = NumberGeneratorContainerState::default();
+
+        for (name, generator) in self.generators.iter() {
+            state
+                .generators
+                .insert(name.clone(), save(name, generator.as_ref())?);
+        }
+
+        if let Some(fallback) = self.fallback.as_ref() {
+            state.fallback = Some(save("fallback", fallback.as_ref())?);
+        }
+
+        Ok(state)
+    }
+
+    ///根据所有生成器的状态计算一个种子，可以用于创建独立但是可以重现的生成器
+    ///
+    ///有生成器无法保存时返回 None，不会改变生成器的状态
+    pub fn derive_seed(&self) -> Option<u64> {
+        let state = self.save_state().ok()?;
+        let mut hasher = DefaultHasher::new();
+        format!("{state:?}").hash(&mut hasher);
+
+        Some(hasher.finish())
+    }
+
+    ///根据保存的状态创建容器
+    pub fn from_state(state: &NumberGeneratorContainerState) -> Self {
+        let mut container = Self::empty();
+
+        for (name, generator) in state.generators.iter() {
+            container
+                .generators
+                .insert(name.clone(), generator.to_generator());
+        }
+
+        container.fallback = state.fallback.as_ref().map(|state| state.to_generator());
+
+        container
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        Chance, NumberGeneratorContainer, NumberGeneratorContainerState, NumberRange,
+        PRD_NUMBER_GENERATOR,
+    };
+
+    #[test]
+    fn test_number_generator_state() {
+        let mut container = NumberGeneratorContainer::seeded(9);
+        let range = NumberRange::new(0, 1000);
+        let chance = Chance::new(0.2)
+            .with_name(PRD_NUMBER_GENERATOR)
+            .with_owner_key(1, "crit");
+
+        for _ in 0..10 {
+            container.generate(&range).unwrap();
+            container.roll(&chance).unwrap();
+        }
+
+        let state = container.save_state().unwrap();
+        let json = serde_json::to_string(&state).unwrap();
+        let state = serde_json::from_str::<NumberGeneratorContainerState>(&json).unwrap();
+        let mut restored = NumberGeneratorContainer::from_state(&state);
+
+        for _ in 0..100 {
+            assert_eq!(container.generate(&range), restored.generate(&range));
+            assert_eq!(container.roll(&chance), restored.roll(&chance));
+        }
+
+        assert!(NumberGeneratorContainer::new().save_state().is_err());
+
+        assert_eq!(container.derive_seed(), restored.derive_seed());
+        assert_ne!(
+            NumberGeneratorContainer::seeded(1).derive_seed(),
+            NumberGeneratorContainer::seeded(2).derive_seed()
+        );
+        assert_eq!(None, NumberGeneratorContainer::new().derive_seed());
+    }
+}
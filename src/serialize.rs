@@ -0,0 +1,29 @@
+///按照键的名称排序序列化 HashMap，保证相同的数据总是得到相同的输出
+pub mod sorted_map {
+    use std::{fmt::Display, hash::Hash};
+
+    use bevy::platform::collections::HashMap;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S, K, V>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        K: Serialize + Display,
+        V: Serialize,
+    {
+        let mut entries = map.iter().collect::<Vec<_>>();
+        entries.sort_by_cached_key(|(key, _)| key.to_string());
+
+        serializer.collect_map(entries)
+    }
+
+    pub fn deserialize<'de, D, K, V>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+        K: Deserialize<'de> + Eq + Hash,
+        V: Deserialize<'de>,
+    {
+        std::collections::HashMap::<K, V>::deserialize(deserializer)
+            .map(|entries| entries.into_iter().collect())
+    }
+}
@@ -5,31 +5,62 @@ pub struct AttributeUtils;
 impl AttributeUtils {
     pub const POWER: &'static str = "power";
     pub const MAX_POWER: &'static str = "max_power";
+    pub const HEALTH: &'static str = "health";
+    pub const MAX_HEALTH: &'static str = "max_health";
+    pub const ATTACK: &'static str = "attack";
+    pub const DEFENSE: &'static str = "defense";
+    ///攻击者的增伤比例
+    pub const DAMAGE_BONUS: &'static str = "damage_bonus";
+    ///目标的伤害抗性比例
+    pub const RESISTANCE: &'static str = "resistance";
+    ///吸收伤害的护盾
+    pub const SHIELD: &'static str = "shield";
+    ///受到命中时反射给攻击者的伤害比例
+    pub const REFLECT: &'static str = "reflect";
+    ///受到命中时对攻击者造成的固定伤害
+    pub const THORNS: &'static str = "thorns";
+    ///吸血的比例
+    pub const LIFESTEAL: &'static str = "lifesteal";
+    ///受到命中时反击的概率
+    pub const COUNTER_CHANCE: &'static str = "counter_chance";
 
     pub fn new_skill_attribute_set() -> AttributeSet {
         let mut set = AttributeSet::default();
 
         let mut power = Attribute::default();
-        power.attribute_name = "power".to_string();
+        power.attribute_name = "power".into();
         power.set_base_value(10.0);
         set.insert_attribute(power);
 
         let mut max_power = Attribute::default();
-        max_power.attribute_name = "max_power".to_string();
+        max_power.attribute_name = "max_power".into();
         max_power.set_base_value(30.0);
         set.insert_attribute(max_power);
 
-        set.insert_constraint(AttributeConstraint {
-            attribute_name: "max_power".to_string(),
-            target_attribute_name: "power".to_string(),
-            constraint_name: "max".to_string(),
-        });
+        set.insert_constraint(AttributeConstraint::new("max_power", "power", "max"));
 
         let mut defense = Attribute::default();
-        defense.attribute_name = "defense".to_string();
+        defense.attribute_name = "defense".into();
         defense.set_base_value(30.0);
         set.insert_attribute(defense);
 
+        let mut health = Attribute::default();
+        health.attribute_name = "health".into();
+        health.set_base_value(100.0);
+        set.insert_attribute(health);
+
+        let mut max_health = Attribute::default();
+        max_health.attribute_name = "max_health".into();
+        max_health.set_base_value(100.0);
+        set.insert_attribute(max_health);
+
+        set.insert_constraint(AttributeConstraint::new("max_health", "health", "ratio"));
+
+        let mut attack = Attribute::default();
+        attack.attribute_name = "attack".into();
+        attack.set_base_value(20.0);
+        set.insert_attribute(attack);
+
         set
     }
 }
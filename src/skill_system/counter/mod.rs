@@ -0,0 +1,48 @@
+use crate::{AttributeUtils, Chance, DamageSkillEffectProcessor, NumberGeneratorContainer};
+
+use super::{
+    SkillComponent, SkillContext, SkillEffect, SkillEffectProcessor, SkillEffectResult, SkillError,
+};
+
+///反击效果，作为反应效果注册时在每个技能之后执行
+///
+///之前的效果命中时，目标以 counter_chance + 目标的反击属性的概率反击施法者，由战斗选择反击的技能
+pub struct CounterSkillEffectProcessor;
+
+impl CounterSkillEffectProcessor {
+    pub const COUNTER_CHANCE: &'static str = "counter_chance";
+
+    ///目标是否反击，由战斗执行反击
+    pub const COUNTER: &'static str = "counter";
+}
+
+impl SkillEffectProcessor for CounterSkillEffectProcessor {
+    fn name() -> String {
+        "counter_skill_effect".to_string()
+    }
+
+    fn process(
+        &self,
+        _caster: &SkillComponent,
+        target: &SkillComponent,
+        skill_effect: &SkillEffect,
+        context: &SkillContext,
+        generators: &mut NumberGeneratorContainer,
+    ) -> Result<SkillEffectResult, SkillError> {
+        let mut res = SkillEffectResult::default();
+
+        let counter_chance = skill_effect.get_number(Self::COUNTER_CHANCE).unwrap_or(0.0)
+            + target
+                .attribute_set
+                .get_current_value(AttributeUtils::COUNTER_CHANCE)
+                .unwrap_or(0.0);
+
+        let counter = counter_chance > 0.0
+            && context.any_bool(DamageSkillEffectProcessor::HIT)
+            && generators.roll(&Chance::new(counter_chance.min(1.0)))?;
+
+        res.set_value(Self::COUNTER, counter);
+
+        Ok(res)
+    }
+}
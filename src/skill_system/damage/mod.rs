@@ -1,14 +1,47 @@
+mod pipeline;
+
+pub use pipeline::*;
+
+use crate::{
+    AttributeUtils, Chance, DEFAULT_NUMBER_GENERATOR, NumberGeneratorContainer, NumberRange,
+    PRD_NUMBER_GENERATOR,
+};
+
 use super::{
     SkillComponent, SkillContext, SkillEffect, SkillEffectProcessor, SkillEffectResult, SkillError,
 };
 
-pub struct DamageSkillEffectProcessor;
+///伤害效果
+///
+///原始伤害 = min_damage 到 max_damage 之间的随机数 + 攻击力 * attack_ratio，之后经过 pipeline 的所有阶段
+///
+///默认的 pipeline 中伤害 = 原始伤害 * 暴击倍率 * (1 + 增伤) * 100 / (100 + 防御力) * (1 - 抗性) - 护盾
+///
+///同一个技能中的多个伤害效果共享目标的护盾，之前的效果吸收的伤害会从护盾中扣除
+#[derive(Default)]
+pub struct DamageSkillEffectProcessor {
+    pub pipeline: DamagePipeline,
+}
+
+impl DamageSkillEffectProcessor {
+    pub const MIN_DAMAGE: &'static str = "min_damage";
+    pub const MAX_DAMAGE: &'static str = "max_damage";
+    pub const ATTACK_RATIO: &'static str = "attack_ratio";
+    pub const HIT_CHANCE: &'static str = "hit_chance";
+    pub const CRIT_CHANCE: &'static str = "crit_chance";
+    pub const CRIT_MULTIPLIER: &'static str = "crit_multiplier";
+
+    pub const DAMAGE: &'static str = "damage";
+    pub const HIT: &'static str = "hit";
+    pub const CRIT: &'static str = "crit";
+}
 
 pub fn new_damage_skill_effect_result() -> SkillEffectResult {
     let mut res = SkillEffectResult::default();
 
     res.set_value("damage", 0);
     res.set_value("hit", false);
+    res.set_value("crit", false);
 
     res
 }
@@ -20,13 +53,150 @@ impl SkillEffectProcessor for DamageSkillEffectProcessor {
 
     fn process(
         &self,
-        _caster: &SkillComponent,
-        _target: &SkillComponent,
-        _skill_effect: &SkillEffect,
-        _context: &SkillContext,
+        caster: &SkillComponent,
+        target: &SkillComponent,
+        skill_effect: &SkillEffect,
+        context: &SkillContext,
+        generators: &mut NumberGeneratorContainer,
     ) -> Result<SkillEffectResult, SkillError> {
-        let res = new_damage_skill_effect_result();
+        let mut res = new_damage_skill_effect_result();
+
+        let hit_chance = skill_effect.get_number(Self::HIT_CHANCE).unwrap_or(1.0);
+
+        if !generators.roll(&Chance::new(hit_chance))? {
+            return Ok(res);
+        }
+
+        let min_damage = skill_effect.get_number(Self::MIN_DAMAGE).unwrap_or(0.0);
+        let max_damage = skill_effect
+            .get_number(Self::MAX_DAMAGE)
+            .unwrap_or(min_damage);
+
+        let range =
+            NumberRange::inclusive(min_damage.max(0.0) as usize, max_damage.max(0.0) as usize)
+                .with_name(DEFAULT_NUMBER_GENERATOR);
+        let mut damage = generators.generate(&range)? as f32;
+
+        let attack = caster
+            .attribute_set
+            .get_current_value(AttributeUtils::ATTACK)
+            .unwrap_or(0.0);
+        damage += attack * skill_effect.get_number(Self::ATTACK_RATIO).unwrap_or(0.0);
+
+        //暴击按照施法者和效果分别记录伪随机的状态，没有所属单位时为普通的随机判定
+        let crit_chance = skill_effect.get_number(Self::CRIT_CHANCE).unwrap_or(0.0);
+        let mut chance = Chance::new(crit_chance).with_name(PRD_NUMBER_GENERATOR);
+
+        if let Some(owner) = caster.owner {
+            chance = chance.with_owner_key(
+                owner,
+                &format!("{}/{}", skill_effect.skill_effect_name, Self::CRIT),
+            );
+        }
+
+        let crit = crit_chance > 0.0 && generators.roll(&chance)?;
+
+        let input = DamageInput {
+            caster,
+            target,
+            skill_effect,
+            crit,
+            shield: DamageInput::remaining_shield(target, context),
+        };
+        damage = self.pipeline.run(damage, &input, &mut res);
+
+        res.set_value(Self::DAMAGE, damage);
+        res.set_value(Self::HIT, true);
+        res.set_value(Self::CRIT, crit);
 
         Ok(res)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        Attribute, AttributeUtils, DamageSkillEffectProcessor, NumberGeneratorContainer,
+        ScriptedNumberGenerator, Skill, SkillComponent, SkillEffect, SkillEffectProcessorContainer,
+    };
+
+    fn new_damage_skill() -> Skill {
+        Skill::new(
+            "slash",
+            vec![
+                SkillEffect::new("damage_skill_effect")
+                    .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, 10.0)
+                    .with_value(DamageSkillEffectProcessor::MAX_DAMAGE, 20.0)
+                    .with_value(DamageSkillEffectProcessor::ATTACK_RATIO, 1.0)
+                    .with_value(DamageSkillEffectProcessor::HIT_CHANCE, 0.9)
+                    .with_value(DamageSkillEffectProcessor::CRIT_CHANCE, 0.5),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_damage_skill_effect_processor() {
+        let mut container = SkillEffectProcessorContainer::new();
+        container.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+
+        let caster = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        let target = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        let skill = new_damage_skill();
+
+        let mut generators = NumberGeneratorContainer::empty();
+        generators.register(
+            "rand",
+            ScriptedNumberGenerator::default()
+                .then_roll(true)
+                .then(15)
+                .then_roll(false),
+        );
+        generators.register("prd", ScriptedNumberGenerator::default().then_roll(true));
+
+        //(15 + 20) * 2 * 100 / 130
+        let context = container
+            .execute(&caster, &target, &skill, &mut generators)
+            .unwrap();
+        let result = context.get_result("damage_skill_effect").unwrap();
+        assert_eq!(Some(53.0), result.get_number("damage"));
+        assert_eq!(Some(true), result.get_bool("crit"));
+
+        let context = container
+            .execute(&caster, &target, &skill, &mut generators)
+            .unwrap();
+        let result = context.get_result("damage_skill_effect").unwrap();
+        assert_eq!(Some(0.0), result.get_number("damage"));
+        assert_eq!(Some(false), result.get_bool("hit"));
+    }
+
+    #[test]
+    fn test_damage_effects_share_shield() {
+        let mut container = SkillEffectProcessorContainer::new();
+        container.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+
+        let caster = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        let mut target = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+
+        let mut shield = Attribute::default();
+        shield.attribute_name = AttributeUtils::SHIELD.into();
+        shield.set_base_value(6.0);
+        target.attribute_set.insert_attribute(shield);
+
+        //每次 13 * 100 / 130 = 10 点伤害
+        let hit = SkillEffect::new("damage_skill_effect")
+            .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, 13.0);
+        let skill = Skill::new("double_slash", vec![hit.clone(), hit]);
+
+        //第一次命中被吸收 6 点，第二次命中时护盾已经耗尽
+        let context = container
+            .execute(
+                &caster,
+                &target,
+                &skill,
+                &mut NumberGeneratorContainer::seeded(0),
+            )
+            .unwrap();
+        assert_eq!(14.0, context.sum_number(DamageSkillEffectProcessor::DAMAGE));
+        assert_eq!(6.0, context.shield_absorbed());
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{
+    AttributeUtils, DamageSkillEffectProcessor, SkillComponent, SkillContext, SkillEffect,
+    SkillEffectResult,
+};
+
+///伤害计算的阶段，按照声明的顺序执行
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DamageStage {
+    ///原始伤害，例如装备增加的固定伤害
+    Raw,
+    ///攻击者的加成，例如暴击和增伤
+    Bonus,
+    ///目标的减免，例如防御和抗性
+    Mitigation,
+    ///护盾吸收
+    Shield,
+    ///最终的取整和限制
+    Clamp,
+}
+
+impl DamageStage {
+    pub const ALL: [DamageStage; 5] = [
+        DamageStage::Raw,
+        DamageStage::Bonus,
+        DamageStage::Mitigation,
+        DamageStage::Shield,
+        DamageStage::Clamp,
+    ];
+
+    ///伤害效果结果中记录这个阶段之后伤害的键
+    pub fn result_key(&self) -> &'static str {
+        match self {
+            DamageStage::Raw => "raw_damage",
+            DamageStage::Bonus => "bonus_damage",
+            DamageStage::Mitigation => "mitigated_damage",
+            DamageStage::Shield => "shielded_damage",
+            DamageStage::Clamp => "clamped_damage",
+        }
+    }
+}
+
+///伤害阶段的输入
+pub struct DamageInput<'a> {
+    pub caster: &'a SkillComponent,
+    pub target: &'a SkillComponent,
+    pub skill_effect: &'a SkillEffect,
+    pub crit: bool,
+    ///目标剩余的护盾，已经扣除同一个技能中之前的效果吸收的伤害
+    pub shield: f32,
+}
+
+impl DamageInput<'_> {
+    ///目标剩余的护盾
+    pub fn remaining_shield(target: &SkillComponent, context: &SkillContext) -> f32 {
+        (attribute(target, AttributeUtils::SHIELD) - context.shield_absorbed()).max(0.0)
+    }
+}
+
+///状态钩子检查的单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageHookSide {
+    Caster,
+    Target,
+}
+
+///只在单位带有某个状态时执行的钩子，例如目标的 "易伤"
+struct StatusDamageHook<T> {
+    side: DamageHookSide,
+    status: String,
+    hook: T,
+}
+
+impl<T: DamageHook> DamageHook for StatusDamageHook<T> {
+    fn apply(&self, damage: f32, input: &DamageInput) -> f32 {
+        let component = match self.side {
+            DamageHookSide::Caster => input.caster,
+            DamageHookSide::Target => input.target,
+        };
+
+        if component.has_status(&self.status) {
+            self.hook.apply(damage, input)
+        } else {
+            damage
+        }
+    }
+}
+
+///伤害阶段的钩子，返回修改后的伤害
+pub trait DamageHook: 'static + Sync + Send {
+    fn apply(&self, damage: f32, input: &DamageInput) -> f32;
+}
+
+impl<F> DamageHook for F
+where
+    F: Fn(f32, &DamageInput) -> f32 + 'static + Sync + Send,
+{
+    fn apply(&self, damage: f32, input: &DamageInput) -> f32 {
+        self(damage, input)
+    }
+}
+
+///分阶段的伤害计算，同一阶段的钩子按照注册的顺序执行
+///
+///钩子注册在处理器上，对所有单位生效；单位的状态通过 register_status 注册的钩子影响伤害，
+///被动和装备通过修改属性（增伤、防御、抗性、护盾）影响内置的钩子
+pub struct DamagePipeline {
+    hooks: Vec<(DamageStage, Box<dyn DamageHook>)>,
+}
+
+impl Default for DamagePipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DamagePipeline {
+    ///护盾吸收的伤害，由战斗扣除目标的护盾
+    pub const SHIELD_ABSORBED: &'static str = "shield_absorbed";
+
+    ///包括内置钩子的伤害计算
+    ///
+    ///暴击倍率和攻击者的增伤，防御和目标的抗性，目标的护盾，最后向下取整并且不小于 0
+    pub fn new() -> Self {
+        let mut pipeline = Self::empty();
+
+        pipeline.register(DamageStage::Bonus, |damage, input: &DamageInput| {
+            if input.crit {
+                damage
+                    * input
+                        .skill_effect
+                        .get_number(DamageSkillEffectProcessor::CRIT_MULTIPLIER)
+                        .unwrap_or(2.0)
+            } else {
+                damage
+            }
+        });
+        pipeline.register(DamageStage::Bonus, |damage, input: &DamageInput| {
+            damage * (1.0 + attribute(input.caster, AttributeUtils::DAMAGE_BONUS))
+        });
+        pipeline.register(DamageStage::Mitigation, |damage, input: &DamageInput| {
+            damage * 100.0 / (100.0 + attribute(input.target, AttributeUtils::DEFENSE).max(0.0))
+        });
+        pipeline.register(DamageStage::Mitigation, |damage, input: &DamageInput| {
+            damage * (1.0 - attribute(input.target, AttributeUtils::RESISTANCE).min(1.0))
+        });
+        pipeline.register(DamageStage::Shield, |damage: f32, input: &DamageInput| {
+            (damage - input.shield).max(0.0)
+        });
+        pipeline.register(DamageStage::Clamp, |damage: f32, _: &DamageInput| {
+            damage.floor().max(0.0)
+        });
+
+        pipeline
+    }
+
+    pub fn empty() -> Self {
+        Self { hooks: vec![] }
+    }
+
+    pub fn register<T: DamageHook>(&mut self, stage: DamageStage, hook: T) {
+        let index = self
+            .hooks
+            .iter()
+            .position(|(hook_stage, _)| *hook_stage > stage)
+            .unwrap_or(self.hooks.len());
+
+        self.hooks.insert(index, (stage, Box::new(hook)));
+    }
+
+    ///注册只在施法者或者目标带有 status 状态时执行的钩子
+    pub fn register_status<T: DamageHook>(
+        &mut self,
+        stage: DamageStage,
+        side: DamageHookSide,
+        status: &str,
+        hook: T,
+    ) {
+        self.register(
+            stage,
+            StatusDamageHook {
+                side,
+                status: status.to_string(),
+                hook,
+            },
+        );
+    }
+
+    ///从原始伤害开始执行所有阶段，每个阶段之后的伤害和护盾吸收的伤害记录在 res 中
+    pub fn run(&self, raw_damage: f32, input: &DamageInput, res: &mut SkillEffectResult) -> f32 {
+        let mut damage = raw_damage;
+        let mut absorbed = 0.0;
+
+        for stage in DamageStage::ALL {
+            let before = damage;
+
+            for (_, hook) in self
+                .hooks
+                .iter()
+                .filter(|(hook_stage, _)| *hook_stage == stage)
+            {
+                damage = hook.apply(damage, input);
+            }
+
+            if stage == DamageStage::Shield {
+                absorbed = (before - damage).max(0.0);
+            }
+
+            res.set_value(stage.result_key(), damage);
+        }
+
+        res.set_value(Self::SHIELD_ABSORBED, absorbed.floor());
+
+        damage
+    }
+}
+
+impl SkillContext {
+    ///同一个技能中护盾已经吸收的伤害
+    pub fn shield_absorbed(&self) -> f32 {
+        self.sum_number(DamagePipeline::SHIELD_ABSORBED)
+    }
+}
+
+fn attribute(component: &SkillComponent, name: &str) -> f32 {
+    component
+        .attribute_set
+        .get_current_value(name)
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        Attribute, AttributeUtils, DamageHookSide, DamageInput, DamagePipeline, DamageStage,
+        SkillComponent, SkillContext, SkillEffect, SkillEffectResult, SkillStatus,
+    };
+
+    #[test]
+    fn test_damage_pipeline() {
+        let caster = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        let mut target = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+
+        let mut shield = Attribute::default();
+        shield.attribute_name = AttributeUtils::SHIELD.into();
+        shield.set_base_value(10.0);
+        target.attribute_set.insert_attribute(shield);
+
+        let skill_effect = SkillEffect::new("damage_skill_effect");
+        let input = DamageInput {
+            caster: &caster,
+            target: &target,
+            skill_effect: &skill_effect,
+            crit: true,
+            shield: DamageInput::remaining_shield(&target, &SkillContext::default()),
+        };
+
+        let mut pipeline = DamagePipeline::new();
+        pipeline.register(DamageStage::Raw, |damage, _: &DamageInput| damage + 15.0);
+        pipeline.register(DamageStage::Mitigation, |damage, _: &DamageInput| {
+            damage * 0.5
+        });
+
+        //((50 + 15) * 2 * 100 / 130 * 0.5) - 10
+        let mut res = SkillEffectResult::default();
+        assert_eq!(40.0, pipeline.run(50.0, &input, &mut res));
+        assert_eq!(Some(65.0), res.get_number("raw_damage"));
+        assert_eq!(Some(130.0), res.get_number("bonus_damage"));
+        assert_eq!(Some(50.0), res.get_number("mitigated_damage"));
+        assert_eq!(Some(40.0), res.get_number("shielded_damage"));
+        assert_eq!(Some(10.0), res.get_number(DamagePipeline::SHIELD_ABSORBED));
+
+        let mut res = SkillEffectResult::default();
+        assert_eq!(50.0, DamagePipeline::empty().run(50.0, &input, &mut res));
+
+        //目标带有易伤状态时伤害翻倍
+        let mut pipeline = DamagePipeline::empty();
+        pipeline.register_status(
+            DamageStage::Mitigation,
+            DamageHookSide::Target,
+            "vulnerable",
+            |damage, _: &DamageInput| damage * 2.0,
+        );
+        assert_eq!(50.0, pipeline.run(50.0, &input, &mut res));
+
+        let mut vulnerable = target.clone();
+        vulnerable.add_status(SkillStatus::new("vulnerable", Some(1)));
+        let input = DamageInput {
+            target: &vulnerable,
+            ..input
+        };
+        assert_eq!(100.0, pipeline.run(50.0, &input, &mut res));
+    }
+}
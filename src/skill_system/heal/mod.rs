@@ -0,0 +1,61 @@
+use crate::{AttributeUtils, DEFAULT_NUMBER_GENERATOR, NumberGeneratorContainer, NumberRange};
+
+use super::{
+    SkillComponent, SkillContext, SkillEffect, SkillEffectProcessor, SkillEffectResult, SkillError,
+};
+
+///治疗效果
+///
+///治疗量 = min_heal 到 max_heal 之间的随机数 + 攻击力 * attack_ratio，不会超过目标损失的生命值
+pub struct HealSkillEffectProcessor;
+
+impl HealSkillEffectProcessor {
+    pub const MIN_HEAL: &'static str = "min_heal";
+    pub const MAX_HEAL: &'static str = "max_heal";
+    pub const ATTACK_RATIO: &'static str = "attack_ratio";
+
+    pub const HEAL: &'static str = "heal";
+}
+
+impl SkillEffectProcessor for HealSkillEffectProcessor {
+    fn name() -> String {
+        "heal_skill_effect".to_string()
+    }
+
+    fn process(
+        &self,
+        caster: &SkillComponent,
+        target: &SkillComponent,
+        skill_effect: &SkillEffect,
+        _context: &SkillContext,
+        generators: &mut NumberGeneratorContainer,
+    ) -> Result<SkillEffectResult, SkillError> {
+        let mut res = SkillEffectResult::default();
+
+        let min_heal = skill_effect.get_number(Self::MIN_HEAL).unwrap_or(0.0);
+        let max_heal = skill_effect.get_number(Self::MAX_HEAL).unwrap_or(min_heal);
+
+        let range = NumberRange::inclusive(min_heal.max(0.0) as usize, max_heal.max(0.0) as usize)
+            .with_name(DEFAULT_NUMBER_GENERATOR);
+        let mut heal = generators.generate(&range)? as f32;
+
+        let attack = caster
+            .attribute_set
+            .get_current_value(AttributeUtils::ATTACK)
+            .unwrap_or(0.0);
+        heal += attack * skill_effect.get_number(Self::ATTACK_RATIO).unwrap_or(0.0);
+
+        let health = target
+            .attribute_set
+            .get_current_value(AttributeUtils::HEALTH)
+            .unwrap_or(0.0);
+        let max_health = target
+            .attribute_set
+            .get_current_value(AttributeUtils::MAX_HEALTH)
+            .unwrap_or(health);
+
+        res.set_value(Self::HEAL, heal.floor().min(max_health - health).max(0.0));
+
+        Ok(res)
+    }
+}
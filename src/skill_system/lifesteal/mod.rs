@@ -0,0 +1,109 @@
+use crate::{AttributeUtils, DamageSkillEffectProcessor, NumberGeneratorContainer};
+
+use super::{
+    SkillComponent, SkillContext, SkillEffect, SkillEffectProcessor, SkillEffectResult, SkillError,
+};
+
+///吸血效果，需要放在伤害效果之后
+///
+///施法者的治疗量 = 之前的效果造成的伤害 * (ratio + 施法者的吸血属性)，不会超过施法者损失的生命值
+pub struct LifestealSkillEffectProcessor;
+
+impl LifestealSkillEffectProcessor {
+    pub const RATIO: &'static str = "ratio";
+
+    ///施法者的治疗量，由战斗增加施法者的生命值
+    pub const CASTER_HEAL: &'static str = "caster_heal";
+}
+
+impl SkillEffectProcessor for LifestealSkillEffectProcessor {
+    fn name() -> String {
+        "lifesteal_skill_effect".to_string()
+    }
+
+    fn process(
+        &self,
+        caster: &SkillComponent,
+        _target: &SkillComponent,
+        skill_effect: &SkillEffect,
+        context: &SkillContext,
+        _generators: &mut NumberGeneratorContainer,
+    ) -> Result<SkillEffectResult, SkillError> {
+        let mut res = SkillEffectResult::default();
+
+        let ratio = skill_effect.get_number(Self::RATIO).unwrap_or(0.0)
+            + caster
+                .attribute_set
+                .get_current_value(AttributeUtils::LIFESTEAL)
+                .unwrap_or(0.0);
+        let heal = context.sum_number(DamageSkillEffectProcessor::DAMAGE) * ratio;
+
+        let health = caster
+            .attribute_set
+            .get_current_value(AttributeUtils::HEALTH)
+            .unwrap_or(0.0);
+        let max_health = caster
+            .attribute_set
+            .get_current_value(AttributeUtils::MAX_HEALTH)
+            .unwrap_or(health);
+
+        res.set_value(
+            Self::CASTER_HEAL,
+            heal.floor().min(max_health - health).max(0.0),
+        );
+
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        AttributeUtils, DamageSkillEffectProcessor, LifestealSkillEffectProcessor,
+        NumberGeneratorContainer, Skill, SkillComponent, SkillEffect,
+        SkillEffectProcessorContainer,
+    };
+
+    #[test]
+    fn test_lifesteal_skill_effect_processor() {
+        let mut container = SkillEffectProcessorContainer::new();
+        container.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+        container.register_skill_effect_processor(LifestealSkillEffectProcessor);
+
+        let mut caster = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        let target = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+
+        let skill = Skill::new(
+            "bite",
+            vec![
+                SkillEffect::new("damage_skill_effect")
+                    .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, 130.0),
+                SkillEffect::new("lifesteal_skill_effect")
+                    .with_value(LifestealSkillEffectProcessor::RATIO, 0.5),
+            ],
+        );
+
+        let execute = |caster: &SkillComponent| {
+            container
+                .execute(
+                    caster,
+                    &target,
+                    &skill,
+                    &mut NumberGeneratorContainer::seeded(1),
+                )
+                .unwrap()
+                .get_result("lifesteal_skill_effect")
+                .unwrap()
+                .get_number(LifestealSkillEffectProcessor::CASTER_HEAL)
+        };
+
+        //满血时不会治疗
+        assert_eq!(Some(0.0), execute(&caster));
+
+        //130 * 100 / 130 = 100，100 * 0.5 超过损失的 30 点生命值
+        caster
+            .attribute_set
+            .set_current_value(AttributeUtils::HEALTH, 70.0, &Default::default());
+        assert_eq!(Some(30.0), execute(&caster));
+    }
+}
@@ -1,9 +1,29 @@
 mod attribute;
+mod counter;
 mod damage;
+mod heal;
+mod lifesteal;
 mod plugin;
+mod preview;
+mod reflect;
 mod skill;
+mod skill_book;
+mod status;
+mod summon;
+mod taunt;
+mod thorns;
 
 pub use attribute::*;
+pub use counter::*;
 pub use damage::*;
+pub use heal::*;
+pub use lifesteal::*;
 pub use plugin::*;
+pub use preview::*;
+pub use reflect::*;
 pub use skill::*;
+pub use skill_book::*;
+pub use status::*;
+pub use summon::*;
+pub use taunt::*;
+pub use thorns::*;
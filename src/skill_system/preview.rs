@@ -0,0 +1,395 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    AttributeUtils, Chance, CounterSkillEffectProcessor, DamageSkillEffectProcessor,
+    HealSkillEffectProcessor, NumberGenerator, NumberGeneratorContainer, NumberGeneratorError,
+    NumberRange, RandNumberGenerator, ReflectSkillEffectProcessor,
+};
+
+use super::{Skill, SkillComponent, SkillContext, SkillEffectProcessorContainer, SkillError};
+
+///枚举时单个范围允许的最大分支数
+pub const MAX_PREVIEW_BRANCHES: usize = 256;
+
+///枚举时允许的最大结果数，超过时改为采样
+pub const MAX_PREVIEW_OUTCOMES: usize = 4096;
+
+///采样时默认的次数
+pub const DEFAULT_PREVIEW_SAMPLES: usize = 256;
+
+///技能预览的结果
+///
+///概率和期望值按照所有结果的权重计算，未命中时伤害为 0
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SkillPreview {
+    pub min_damage: f32,
+    pub expected_damage: f32,
+    pub max_damage: f32,
+    pub min_heal: f32,
+    pub expected_heal: f32,
+    pub max_heal: f32,
+    pub hit_chance: f32,
+    pub crit_chance: f32,
+    ///目标的反射和荆棘对施法者造成的伤害
+    pub expected_caster_damage: f32,
+    ///目标反击的概率
+    pub counter_chance: f32,
+    ///伤害不低于目标当前生命值的概率
+    pub lethal_chance: f32,
+    ///参与计算的结果数
+    pub outcomes: usize,
+    ///是否枚举了所有结果，false 表示结果来自采样
+    pub exact: bool,
+}
+
+impl SkillPreview {
+    fn new(exact: bool) -> Self {
+        Self {
+            min_damage: f32::MAX,
+            max_damage: f32::MIN,
+            min_heal: f32::MAX,
+            max_heal: f32::MIN,
+            exact,
+            ..Default::default()
+        }
+    }
+
+    fn add_outcome(&mut self, context: &SkillContext, health: f32, weight: f32) {
+        let damage = context.sum_number(DamageSkillEffectProcessor::DAMAGE);
+        let heal = context.sum_number(HealSkillEffectProcessor::HEAL);
+
+        self.min_damage = self.min_damage.min(damage);
+        self.max_damage = self.max_damage.max(damage);
+        self.min_heal = self.min_heal.min(heal);
+        self.max_heal = self.max_heal.max(heal);
+
+        self.expected_damage += damage * weight;
+        self.expected_heal += heal * weight;
+        self.expected_caster_damage +=
+            context.sum_number(ReflectSkillEffectProcessor::CASTER_DAMAGE) * weight;
+
+        if context.any_bool(CounterSkillEffectProcessor::COUNTER) {
+            self.counter_chance += weight;
+        }
+
+        if context.any_bool(DamageSkillEffectProcessor::HIT) {
+            self.hit_chance += weight;
+        }
+
+        if context.any_bool(DamageSkillEffectProcessor::CRIT) {
+            self.crit_chance += weight;
+        }
+
+        if damage > 0.0 && damage >= health {
+            self.lethal_chance += weight;
+        }
+
+        self.outcomes += 1;
+    }
+
+    fn finish(mut self) -> Self {
+        if self.outcomes == 0 {
+            return Self {
+                exact: self.exact,
+                ..Default::default()
+            };
+        }
+
+        if !self.exact {
+            let samples = self.outcomes as f32;
+
+            self.expected_damage /= samples;
+            self.expected_heal /= samples;
+            self.expected_caster_damage /= samples;
+            self.counter_chance /= samples;
+            self.hit_chance /= samples;
+            self.crit_chance /= samples;
+            self.lethal_chance /= samples;
+        }
+
+        self
+    }
+}
+
+///枚举中的一条路径，prefix 为之前选择的分支
+#[derive(Default)]
+struct PreviewPath {
+    prefix: Vec<usize>,
+    choices: Vec<(usize, usize)>,
+    probability: f32,
+    overflow: bool,
+}
+
+impl PreviewPath {
+    fn choose(&mut self, count: usize) -> usize {
+        let choice = self.prefix.get(self.choices.len()).copied().unwrap_or(0);
+        self.choices.push((choice, count));
+
+        choice
+    }
+
+    ///下一条路径的前缀，所有路径都已经枚举时返回 None
+    fn next_prefix(mut self) -> Option<Vec<usize>> {
+        while let Some((choice, count)) = self.choices.pop() {
+            if choice + 1 < count {
+                self.choices.push((choice + 1, count));
+
+                return Some(self.choices.iter().map(|(choice, _)| *choice).collect());
+            }
+        }
+
+        None
+    }
+}
+
+///按照路径选择分支的生成器
+///
+///概率判定直接使用 chance.probability，不会考虑伪随机分布等生成器的状态
+struct PreviewNumberGenerator(Rc<RefCell<PreviewPath>>);
+
+impl NumberGenerator for PreviewNumberGenerator {
+    fn generate(&mut self, range: &NumberRange) -> Result<usize, NumberGeneratorError> {
+        let mut path = self.0.borrow_mut();
+
+        if range.len() > MAX_PREVIEW_BRANCHES {
+            path.overflow = true;
+
+            return Ok(range.start());
+        }
+
+        let choice = path.choose(range.len());
+        path.probability /= range.len() as f32;
+
+        Ok(range.start() + choice)
+    }
+
+    fn roll(&mut self, chance: &Chance) -> Result<bool, NumberGeneratorError> {
+        let probability = chance.probability.clamp(0.0, 1.0);
+
+        if probability >= 1.0 {
+            return Ok(true);
+        }
+
+        if probability <= 0.0 {
+            return Ok(false);
+        }
+
+        let mut path = self.0.borrow_mut();
+
+        if path.choose(2) == 0 {
+            path.probability *= probability;
+            Ok(true)
+        } else {
+            path.probability *= 1.0 - probability;
+            Ok(false)
+        }
+    }
+}
+
+impl SkillEffectProcessorContainer {
+    ///预览技能的效果，不会修改施法者和目标
+    ///
+    ///结果过多时使用新的随机数生成器采样 DEFAULT_PREVIEW_SAMPLES 次
+    pub fn preview(
+        &self,
+        caster: &SkillComponent,
+        target: &SkillComponent,
+        skill: &Skill,
+    ) -> Result<SkillPreview, SkillError> {
+        let mut generators = NumberGeneratorContainer::empty();
+        generators.set_fallback(RandNumberGenerator::new());
+
+        self.preview_with(
+            caster,
+            target,
+            skill,
+            &mut generators,
+            DEFAULT_PREVIEW_SAMPLES,
+        )
+    }
+
+    ///预览技能的效果，结果过多时使用 generators 采样 samples 次
+    ///
+    ///技能不会修改施法者和目标，generators 不应该是战斗中使用的容器
+    pub fn preview_with(
+        &self,
+        caster: &SkillComponent,
+        target: &SkillComponent,
+        skill: &Skill,
+        generators: &mut NumberGeneratorContainer,
+        samples: usize,
+    ) -> Result<SkillPreview, SkillError> {
+        match self.enumerate_preview(caster, target, skill)? {
+            Some(preview) => Ok(preview),
+            None => self.sample_preview(caster, target, skill, generators, samples),
+        }
+    }
+
+    ///使用 generators 执行技能 samples 次，统计结果
+    pub fn sample_preview(
+        &self,
+        caster: &SkillComponent,
+        target: &SkillComponent,
+        skill: &Skill,
+        generators: &mut NumberGeneratorContainer,
+        samples: usize,
+    ) -> Result<SkillPreview, SkillError> {
+        let health = target_health(target);
+
+        let mut preview = SkillPreview::new(false);
+
+        for _ in 0..samples.max(1) {
+            let context = self.execute(caster, target, skill, generators)?;
+            preview.add_outcome(&context, health, 1.0);
+        }
+
+        Ok(preview.finish())
+    }
+
+    ///枚举所有结果，结果过多时返回 None
+    fn enumerate_preview(
+        &self,
+        caster: &SkillComponent,
+        target: &SkillComponent,
+        skill: &Skill,
+    ) -> Result<Option<SkillPreview>, SkillError> {
+        let health = target_health(target);
+
+        let mut preview = SkillPreview::new(true);
+        let mut prefix = vec![];
+
+        loop {
+            let path = Rc::new(RefCell::new(PreviewPath {
+                prefix,
+                probability: 1.0,
+                ..Default::default()
+            }));
+
+            let mut generators = NumberGeneratorContainer::empty();
+            generators.set_fallback(PreviewNumberGenerator(path.clone()));
+
+            let context = self.execute(caster, target, skill, &mut generators)?;
+            let path = path.take();
+
+            if path.overflow || preview.outcomes >= MAX_PREVIEW_OUTCOMES {
+                return Ok(None);
+            }
+
+            preview.add_outcome(&context, health, path.probability);
+
+            match path.next_prefix() {
+                Some(next) => prefix = next,
+                None => break,
+            }
+        }
+
+        Ok(Some(preview.finish()))
+    }
+}
+
+fn target_health(target: &SkillComponent) -> f32 {
+    target
+        .attribute_set
+        .get_current_value(AttributeUtils::HEALTH)
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        Attribute, AttributeUtils, CounterSkillEffectProcessor, DamageSkillEffectProcessor,
+        NumberGeneratorContainer, Skill, SkillComponent, SkillEffect,
+        SkillEffectProcessorContainer, ThornsSkillEffectProcessor,
+    };
+
+    #[test]
+    fn test_skill_preview() {
+        let mut container = SkillEffectProcessorContainer::new();
+        container.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+
+        let caster = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        let target = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+
+        let skill = Skill::new(
+            "slash",
+            vec![
+                SkillEffect::new("damage_skill_effect")
+                    .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, 110.0)
+                    .with_value(DamageSkillEffectProcessor::MAX_DAMAGE, 240.0)
+                    .with_value(DamageSkillEffectProcessor::HIT_CHANCE, 0.5)
+                    .with_value(DamageSkillEffectProcessor::CRIT_CHANCE, 0.5),
+            ],
+        );
+
+        //未命中 1 种，命中后 131 个伤害值各有暴击和不暴击两种
+        let preview = container.preview(&caster, &target, &skill).unwrap();
+        assert!(preview.exact);
+        assert_eq!(263, preview.outcomes);
+        assert_eq!(0.0, preview.min_damage);
+        assert_eq!(369.0, preview.max_damage);
+        assert!((preview.hit_chance - 0.5).abs() < 1e-4);
+        assert!((preview.crit_chance - 0.25).abs() < 1e-4);
+        assert!(preview.expected_damage > 0.0 && preview.expected_damage < 369.0);
+        assert!(preview.lethal_chance > 0.0 && preview.lethal_chance < 0.5);
+
+        assert_eq!(
+            Some(100.0),
+            target.attribute_set.get_current_value("health")
+        );
+
+        let skill = Skill::new(
+            "slash",
+            vec![
+                SkillEffect::new("damage_skill_effect")
+                    .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, 0.0)
+                    .with_value(DamageSkillEffectProcessor::MAX_DAMAGE, 1000.0),
+            ],
+        );
+
+        let mut generators = NumberGeneratorContainer::new();
+        let preview = container
+            .preview_with(&caster, &target, &skill, &mut generators, 16)
+            .unwrap();
+        assert!(!preview.exact);
+        assert_eq!(16, preview.outcomes);
+        assert_eq!(1.0, preview.hit_chance);
+        assert!(preview.max_damage <= 769.0);
+    }
+
+    #[test]
+    fn test_reaction_preview() {
+        let mut container = SkillEffectProcessorContainer::new();
+        container.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+        container.register_reaction_processor(ThornsSkillEffectProcessor::default());
+        container.register_reaction_processor(CounterSkillEffectProcessor);
+
+        let caster = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        let mut target = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+
+        for (name, value) in [
+            (AttributeUtils::THORNS, 13.0),
+            (AttributeUtils::COUNTER_CHANCE, 0.5),
+        ] {
+            let mut attribute = Attribute::default();
+            attribute.attribute_name = name.into();
+            attribute.set_base_value(value);
+            target.attribute_set.insert_attribute(attribute);
+        }
+
+        let skill = Skill::new(
+            "slash",
+            vec![
+                SkillEffect::new("damage_skill_effect")
+                    .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, 13.0)
+                    .with_value(DamageSkillEffectProcessor::HIT_CHANCE, 0.5),
+            ],
+        );
+
+        //命中时受到 13 * 100 / 130 的荆棘伤害，并且有一半的概率被反击
+        let preview = container.preview(&caster, &target, &skill).unwrap();
+        assert!(preview.exact);
+        assert_eq!(3, preview.outcomes);
+        assert!((preview.expected_caster_damage - 5.0).abs() < 1e-4);
+        assert!((preview.counter_chance - 0.25).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{
+    AttributeUtils, DamageInput, DamagePipeline, DamageSkillEffectProcessor,
+    NumberGeneratorContainer,
+};
+
+use super::{
+    SkillComponent, SkillContext, SkillEffect, SkillEffectProcessor, SkillEffectResult, SkillError,
+};
+
+///反射效果，作为反应效果注册时在每个技能之后执行
+///
+///返还给施法者的伤害 = 之前的效果造成的伤害 * (ratio + 目标的反射属性)，之后经过 pipeline 的所有阶段
+#[derive(Default)]
+pub struct ReflectSkillEffectProcessor {
+    pub pipeline: DamagePipeline,
+}
+
+impl ReflectSkillEffectProcessor {
+    pub const RATIO: &'static str = "ratio";
+
+    ///施法者受到的伤害，由战斗扣除施法者的生命值
+    pub const CASTER_DAMAGE: &'static str = "caster_damage";
+    ///施法者的护盾吸收的伤害，由战斗扣除施法者的护盾
+    pub const CASTER_SHIELD_ABSORBED: &'static str = "caster_shield_absorbed";
+
+    ///目标对施法者造成 raw_damage 的伤害
+    ///
+    ///pipeline 中的 caster 为做出反应的目标，target 为施法者，施法者的防御和护盾同样生效
+    pub(crate) fn retaliate(
+        pipeline: &DamagePipeline,
+        raw_damage: f32,
+        caster: &SkillComponent,
+        target: &SkillComponent,
+        skill_effect: &SkillEffect,
+        context: &SkillContext,
+    ) -> SkillEffectResult {
+        let mut res = SkillEffectResult::default();
+        res.set_value(Self::CASTER_DAMAGE, 0.0);
+        res.set_value(Self::CASTER_SHIELD_ABSORBED, 0.0);
+
+        if raw_damage <= 0.0 {
+            return res;
+        }
+
+        let shield = caster
+            .attribute_set
+            .get_current_value(AttributeUtils::SHIELD)
+            .unwrap_or(0.0)
+            - context.sum_number(Self::CASTER_SHIELD_ABSORBED);
+        let input = DamageInput {
+            caster: target,
+            target: caster,
+            skill_effect,
+            crit: false,
+            shield: shield.max(0.0),
+        };
+
+        let mut stages = SkillEffectResult::default();
+        let damage = pipeline.run(raw_damage, &input, &mut stages);
+
+        res.set_value(Self::CASTER_DAMAGE, damage);
+        res.set_value(
+            Self::CASTER_SHIELD_ABSORBED,
+            stages
+                .get_number(DamagePipeline::SHIELD_ABSORBED)
+                .unwrap_or(0.0),
+        );
+
+        res
+    }
+}
+
+impl SkillEffectProcessor for ReflectSkillEffectProcessor {
+    fn name() -> String {
+        "reflect_skill_effect".to_string()
+    }
+
+    fn process(
+        &self,
+        caster: &SkillComponent,
+        target: &SkillComponent,
+        skill_effect: &SkillEffect,
+        context: &SkillContext,
+        _generators: &mut NumberGeneratorContainer,
+    ) -> Result<SkillEffectResult, SkillError> {
+        let ratio = skill_effect.get_number(Self::RATIO).unwrap_or(0.0)
+            + target
+                .attribute_set
+                .get_current_value(AttributeUtils::REFLECT)
+                .unwrap_or(0.0);
+        let damage = context.sum_number(DamageSkillEffectProcessor::DAMAGE) * ratio;
+
+        Ok(Self::retaliate(
+            &self.pipeline,
+            damage,
+            caster,
+            target,
+            skill_effect,
+            context,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        Attribute, AttributeUtils, DamageSkillEffectProcessor, NumberGeneratorContainer,
+        ReflectSkillEffectProcessor, Skill, SkillComponent, SkillEffect,
+        SkillEffectProcessorContainer, ThornsSkillEffectProcessor,
+    };
+
+    fn insert_attribute(component: &mut SkillComponent, name: &str, value: f32) {
+        let mut attribute = Attribute::default();
+        attribute.attribute_name = name.into();
+        attribute.set_base_value(value);
+        component.attribute_set.insert_attribute(attribute);
+    }
+
+    #[test]
+    fn test_reflect_skill_effect_processor() {
+        let mut container = SkillEffectProcessorContainer::new();
+        container.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+        container.register_reaction_processor(ReflectSkillEffectProcessor::default());
+        container.register_reaction_processor(ThornsSkillEffectProcessor::default());
+
+        let mut caster = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        let mut target = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        insert_attribute(&mut target, AttributeUtils::REFLECT, 0.5);
+        insert_attribute(&mut target, AttributeUtils::THORNS, 13.0);
+
+        let slash = SkillEffect::new("damage_skill_effect")
+            .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, 65.0);
+        let execute = |caster: &SkillComponent, skill: &Skill| {
+            container
+                .execute(
+                    caster,
+                    &target,
+                    skill,
+                    &mut NumberGeneratorContainer::seeded(1),
+                )
+                .unwrap()
+        };
+
+        //反应效果由目标的属性决定，不需要写在技能中
+        //65 * 100 / 130 = 50，反射 50 * 0.5 = 25，经过施法者的防御 25 * 100 / 130
+        let skill = Skill::new("slash", vec![slash.clone()]);
+        let context = execute(&caster, &skill);
+        let reflect = context.get_result("reflect_skill_effect").unwrap();
+        assert_eq!(
+            Some(19.0),
+            reflect.get_number(ReflectSkillEffectProcessor::CASTER_DAMAGE)
+        );
+        //荆棘 13 * 100 / 130
+        assert_eq!(
+            29.0,
+            context.sum_number(ReflectSkillEffectProcessor::CASTER_DAMAGE)
+        );
+
+        //技能中的同名效果增加反射的比例，不会重复执行
+        let skill = Skill::new(
+            "slash",
+            vec![
+                slash,
+                SkillEffect::new("reflect_skill_effect")
+                    .with_value(ReflectSkillEffectProcessor::RATIO, 0.5),
+            ],
+        );
+        let context = execute(&caster, &skill);
+        assert_eq!(
+            Some(38.0),
+            context
+                .get_result("reflect_skill_effect")
+                .unwrap()
+                .get_number(ReflectSkillEffectProcessor::CASTER_DAMAGE)
+        );
+
+        //施法者的护盾依次吸收反射和荆棘的伤害
+        insert_attribute(&mut caster, AttributeUtils::SHIELD, 30.0);
+        let context = execute(&caster, &skill);
+        assert_eq!(
+            30.0,
+            context.sum_number(ReflectSkillEffectProcessor::CASTER_SHIELD_ABSORBED)
+        );
+        assert_eq!(
+            18.0,
+            context.sum_number(ReflectSkillEffectProcessor::CASTER_DAMAGE)
+        );
+
+        //未命中时没有反应
+        let skill = Skill::new(
+            "miss",
+            vec![
+                SkillEffect::new("damage_skill_effect")
+                    .with_value(DamageSkillEffectProcessor::HIT_CHANCE, 0.0),
+            ],
+        );
+        let context = execute(&caster, &skill);
+        assert_eq!(
+            0.0,
+            context.sum_number(ReflectSkillEffectProcessor::CASTER_DAMAGE)
+        );
+    }
+}
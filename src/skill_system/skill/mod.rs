@@ -1,22 +1,373 @@
-use bevy::platform::collections::HashMap;
+mod scaling;
 
-use crate::AttributeSet;
+pub use scaling::*;
 
+use std::borrow::Cow;
+
+use bevy::{ecs::component::Component, platform::collections::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AttributeConstraintProcessorContainer, AttributeSet, AttributeUtils, IdLookup,
+    NumberGeneratorContainer, NumberGeneratorError, SkillBook, SkillStatus, SkillUnavailable,
+    define_id,
+};
+
+define_id!(
+    ///技能效果标识符
+    SkillEffectId
+);
+
+define_id!(
+    ///技能效果参数和结果的键
+    SkillPropertyId
+);
+
+define_id!(
+    ///技能的标签，例如 physical 和 magic，常用的标签见 SkillTags
+    SkillTag
+);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Skill {
     pub skill_name: String,
     pub display_name: String,
     pub effcts: Vec<SkillEffect>,
+    #[serde(default)]
+    pub casting: SkillCasting,
+    ///技能等级，从 1 开始，效果参数按照 SkillEffect::scaling 随等级变化
+    #[serde(default = "Skill::default_level")]
+    pub level: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<SkillTag>,
+    #[serde(default)]
+    pub target: SkillTarget,
+}
+
+impl Skill {
+    ///技能和技能数据的默认等级
+    pub(crate) fn default_level() -> u32 {
+        1
+    }
+
+    ///1 级的立即释放的技能，显示名称与技能名称相同
+    pub fn new(skill_name: &str, effcts: Vec<SkillEffect>) -> Self {
+        Self {
+            skill_name: skill_name.to_string(),
+            display_name: skill_name.to_string(),
+            effcts,
+            casting: SkillCasting::default(),
+            level: Self::default_level(),
+            tags: vec![],
+            target: SkillTarget::default(),
+        }
+    }
+
+    pub fn with_casting(mut self, casting: SkillCasting) -> Self {
+        self.casting = casting;
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<SkillTag>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn with_target(mut self, target: SkillTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    ///升级技能，返回新的等级
+    pub fn upgrade(&mut self) -> u32 {
+        self.level = self.level.max(1) + 1;
+        self.level
+    }
+
+    pub fn has_tag(&self, tag: impl IdLookup<SkillTag>) -> bool {
+        tag.lookup().is_some_and(|tag| self.tags.contains(&tag))
+    }
+}
+
+///技能的打断条件
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct SkillInterrupt {
+    ///开始之后受到的伤害达到这个值时打断，None 表示不会被伤害打断
+    #[serde(default)]
+    pub damage_threshold: Option<f32>,
+    ///为 true 时不会被眩晕等控制效果打断
+    #[serde(default)]
+    pub uninterruptible: bool,
+}
+
+///技能可以选择的目标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkillTarget {
+    ///任意存活的单位
+    #[default]
+    Any,
+    ///不同阵营的存活单位
+    Enemy,
+    ///同一阵营的存活单位，包括施法者
+    Ally,
+    ///只能选择施法者
+    #[serde(rename = "self")]
+    Caster,
+    ///同一阵营的死亡单位，例如复活
+    DeadAlly,
+}
+
+impl SkillTarget {
+    ///caster 是否可以选择 target
+    ///
+    ///施法者和目标的 owner 相同时视为选择自己，没有 owner 时为同一个组件时视为选择自己
+    ///
+    ///没有生命值属性的目标视为存活
+    pub fn allows(&self, caster: &SkillComponent, target: &SkillComponent) -> bool {
+        let alive = target
+            .attribute_set
+            .get_current_value(AttributeUtils::HEALTH)
+            .is_none_or(|health| health > 0.0);
+        let ally = caster.faction == target.faction;
+
+        match self {
+            SkillTarget::Any => alive,
+            SkillTarget::Enemy => alive && !ally,
+            SkillTarget::Ally => alive && ally,
+            SkillTarget::Caster => {
+                alive
+                    && match (caster.owner, target.owner) {
+                        (Some(caster), Some(target)) => caster == target,
+                        _ => std::ptr::eq(caster, target),
+                    }
+            }
+            SkillTarget::DeadAlly => !alive && ally,
+        }
+    }
 }
 
+///技能的释放方式
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SkillCasting {
+    ///立即释放
+    #[default]
+    Instant,
+    ///蓄力 turns 个回合之后释放
+    Charge {
+        turns: u32,
+        #[serde(default)]
+        interrupt: SkillInterrupt,
+    },
+    ///从开始的回合起 turns 个回合中每个回合释放一次
+    Channel {
+        turns: u32,
+        #[serde(default)]
+        interrupt: SkillInterrupt,
+    },
+}
+
+#[derive(Debug, Clone, Component)]
 pub struct SkillComponent {
     pub attribute_set: AttributeSet,
+    pub skill_book: SkillBook,
+    pub statuses: Vec<SkillStatus>,
+    ///阵营，同一阵营的单位互为友方，战斗中为单位的队伍
+    pub faction: usize,
+    ///所属单位的标识符，战斗中为单位的标识符，用于记录伪随机判定的状态
+    pub owner: Option<u32>,
 }
 
-pub enum SkillError {}
+impl SkillComponent {
+    pub fn new(attribute_set: AttributeSet) -> Self {
+        Self {
+            attribute_set,
+            skill_book: SkillBook::default(),
+            statuses: vec![],
+            faction: 0,
+            owner: None,
+        }
+    }
+
+    pub fn with_owner(mut self, owner: u32) -> Self {
+        self.owner = Some(owner);
+        self
+    }
 
+    ///技能书中装备的技能，按照装备的顺序
+    pub fn skills(&self) -> impl Iterator<Item = &Skill> {
+        self.skill_book.equipped().map(|learned| &learned.skill)
+    }
+
+    ///添加状态，已经存在同名的状态时替换它
+    pub fn add_status(&mut self, status: SkillStatus) {
+        self.remove_status(&status.name);
+        self.statuses.push(status);
+    }
+
+    pub fn remove_status(&mut self, name: &str) -> Option<SkillStatus> {
+        let index = self
+            .statuses
+            .iter()
+            .position(|status| status.name == name)?;
+
+        Some(self.statuses.remove(index))
+    }
+
+    pub fn has_status(&self, name: &str) -> bool {
+        self.status(name).is_some()
+    }
+
+    pub fn status(&self, name: &str) -> Option<&SkillStatus> {
+        self.statuses.iter().find(|status| status.name == name)
+    }
+
+    ///眩晕的状态，没有眩晕时返回 None
+    pub fn stunned_by(&self) -> Option<&SkillStatus> {
+        self.statuses.iter().find(|status| status.stun)
+    }
+
+    ///经过一个回合，返回结束的状态
+    pub fn tick_statuses(&mut self) -> Vec<SkillStatus> {
+        let mut expired = vec![];
+
+        for status in self.statuses.iter_mut() {
+            if let Some(turns) = status.remaining_turns.as_mut() {
+                *turns = turns.saturating_sub(1);
+            }
+        }
+
+        self.statuses.retain(|status| {
+            let active = status.remaining_turns != Some(0);
+
+            if !active {
+                expired.push(status.clone());
+            }
+
+            active
+        });
+
+        expired
+    }
+
+    ///检查技能是否被状态禁用
+    pub fn check_tags(&self, skill: &Skill) -> Result<(), SkillError> {
+        for status in self.statuses.iter() {
+            if let Some(tag) = status.blocked_tag(skill) {
+                return Err(SkillError::Blocked {
+                    skill_name: skill.skill_name.clone(),
+                    status: status.name.clone(),
+                    tag,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    ///检查技能现在是否可以使用，没有记录在技能书中的技能只受到状态的限制
+    pub fn can_use(&self, skill: &Skill) -> Result<(), SkillUnavailable> {
+        if let Some(status) = self.stunned_by() {
+            return Err(SkillUnavailable::Stunned(status.name.clone()));
+        }
+
+        if let Err(SkillError::Blocked { status, tag, .. }) = self.check_tags(skill) {
+            return Err(SkillUnavailable::Blocked { status, tag });
+        }
+
+        match self.skill_book.get(&skill.skill_name) {
+            Some(_) => self
+                .skill_book
+                .can_use(&skill.skill_name, &self.attribute_set),
+            None => Ok(()),
+        }
+    }
+
+    ///现在可以使用的装备的技能，考虑冷却、消耗和沉默等状态
+    pub fn usable_skills(&self) -> impl Iterator<Item = &Skill> {
+        self.skills().filter(|skill| self.can_use(skill).is_ok())
+    }
+
+    ///使用技能，技能书中的技能扣除消耗并开始冷却
+    pub fn use_skill(
+        &mut self,
+        skill: &Skill,
+        container: &AttributeConstraintProcessorContainer,
+    ) -> Result<(), SkillUnavailable> {
+        self.can_use(skill)?;
+
+        match self.skill_book.get(&skill.skill_name) {
+            Some(_) => {
+                self.skill_book
+                    .use_skill(&skill.skill_name, &mut self.attribute_set, container)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SkillError {
+    ///技能效果使用的随机数生成器出错
+    NumberGenerator(NumberGeneratorError),
+    ///技能效果参数的成长方式无法计算，例如表达式有错误
+    InvalidScaling(String),
+    ///施法者的状态禁用了带有 tag 标签的技能，例如沉默禁用法术
+    Blocked {
+        skill_name: String,
+        status: String,
+        tag: SkillTag,
+    },
+    ///目标不满足技能的目标规则，例如对敌方使用治疗
+    InvalidTarget {
+        skill_name: String,
+        target: SkillTarget,
+    },
+}
+
+impl From<NumberGeneratorError> for SkillError {
+    fn from(value: NumberGeneratorError) -> Self {
+        SkillError::NumberGenerator(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
 pub enum SkillProperty {
     Number(f32),
     Bool(bool),
+    ///名称，例如召唤物的模板
+    Text(String),
+}
+
+impl SkillProperty {
+    pub fn as_number(&self) -> Option<f32> {
+        match self {
+            SkillProperty::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            SkillProperty::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            SkillProperty::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl From<f32> for SkillProperty {
+    fn from(value: f32) -> Self {
+        Self::Number(value)
+    }
 }
 
 impl From<usize> for SkillProperty {
@@ -31,31 +382,177 @@ impl From<bool> for SkillProperty {
     }
 }
 
+impl From<&str> for SkillProperty {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_string())
+    }
+}
+
+impl From<String> for SkillProperty {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SkillEffect {
-    pub skill_effect_name: String,
-    pub payload: HashMap<String, SkillProperty>,
+    pub skill_effect_name: SkillEffectId,
+    #[serde(with = "crate::serialize::sorted_map")]
+    pub payload: HashMap<SkillPropertyId, SkillProperty>,
+    ///随技能等级变化的参数，会覆盖 payload 中对应的值
+    #[serde(
+        default,
+        with = "crate::serialize::sorted_map",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub scaling: HashMap<SkillPropertyId, SkillScaling>,
+}
+
+impl SkillEffect {
+    pub fn new(skill_effect_name: impl Into<SkillEffectId>) -> Self {
+        Self {
+            skill_effect_name: skill_effect_name.into(),
+            payload: Default::default(),
+            scaling: Default::default(),
+        }
+    }
+
+    pub fn with_scaling(mut self, key: impl Into<SkillPropertyId>, scaling: SkillScaling) -> Self {
+        self.scaling.insert(key.into(), scaling);
+        self
+    }
+
+    ///level 级时的效果，没有随等级变化的参数时不会复制
+    pub fn at_level(&self, level: u32) -> Result<Cow<'_, SkillEffect>, SkillError> {
+        if self.scaling.is_empty() {
+            return Ok(Cow::Borrowed(self));
+        }
+
+        let mut skill_effect = self.clone();
+
+        for (key, scaling) in self.scaling.iter() {
+            let base = self.get_number(*key).unwrap_or(0.0);
+            let value = scaling
+                .value(level, base)
+                .map_err(SkillError::InvalidScaling)?;
+
+            skill_effect.payload.insert(*key, value.into());
+        }
+
+        Ok(Cow::Owned(skill_effect))
+    }
+
+    pub fn with_value(
+        mut self,
+        key: impl Into<SkillPropertyId>,
+        value: impl Into<SkillProperty>,
+    ) -> Self {
+        self.payload.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn get_value(&self, key: impl IdLookup<SkillPropertyId>) -> Option<&SkillProperty> {
+        self.payload.get(&key.lookup()?)
+    }
+
+    pub fn get_number(&self, key: impl IdLookup<SkillPropertyId>) -> Option<f32> {
+        self.get_value(key).and_then(|v| v.as_number())
+    }
+
+    pub fn get_bool(&self, key: impl IdLookup<SkillPropertyId>) -> Option<bool> {
+        self.get_value(key).and_then(|v| v.as_bool())
+    }
+
+    pub fn get_text(&self, key: impl IdLookup<SkillPropertyId>) -> Option<&str> {
+        self.get_value(key).and_then(|v| v.as_text())
+    }
 }
 
 #[derive(Default)]
 pub struct SkillEffectResult {
-    payload: HashMap<String, SkillProperty>,
+    payload: HashMap<SkillPropertyId, SkillProperty>,
 }
 
 impl SkillEffectResult {
-    pub fn set_value(&mut self, key: &str, value: impl Into<SkillProperty>) {
+    pub fn set_value(&mut self, key: impl Into<SkillPropertyId>, value: impl Into<SkillProperty>) {
         let value = value.into();
 
-        self.payload.insert(key.to_string(), value);
+        self.payload.insert(key.into(), value);
+    }
+
+    pub fn get_value(&self, key: impl IdLookup<SkillPropertyId>) -> Option<&SkillProperty> {
+        self.payload.get(&key.lookup()?)
+    }
+
+    pub fn get_number(&self, key: impl IdLookup<SkillPropertyId>) -> Option<f32> {
+        self.get_value(key).and_then(|v| v.as_number())
+    }
+
+    pub fn get_bool(&self, key: impl IdLookup<SkillPropertyId>) -> Option<bool> {
+        self.get_value(key).and_then(|v| v.as_bool())
+    }
+
+    pub fn get_text(&self, key: impl IdLookup<SkillPropertyId>) -> Option<&str> {
+        self.get_value(key).and_then(|v| v.as_text())
     }
 
-    pub fn get_value(&self, key: &str) -> Option<&SkillProperty> {
-        self.payload.get(key)
+    pub fn iter(&self) -> impl Iterator<Item = (&SkillPropertyId, &SkillProperty)> {
+        self.payload.iter()
+    }
+
+    ///合并同名效果的结果，数字相加，布尔值取或，其它的值被替换
+    pub fn merge(&mut self, other: SkillEffectResult) {
+        for (key, value) in other.payload {
+            let merged = match (self.payload.get(&key), &value) {
+                (Some(SkillProperty::Number(a)), SkillProperty::Number(b)) => {
+                    SkillProperty::Number(a + b)
+                }
+                (Some(SkillProperty::Bool(a)), SkillProperty::Bool(b)) => {
+                    SkillProperty::Bool(*a || *b)
+                }
+                _ => value,
+            };
+
+            self.payload.insert(key, merged);
+        }
     }
 }
 
 #[derive(Default)]
 pub struct SkillContext {
-    pub results: HashMap<String, SkillEffectResult>,
+    pub results: HashMap<SkillEffectId, SkillEffectResult>,
+}
+
+impl SkillContext {
+    pub fn get_result(
+        &self,
+        skill_effect_name: impl IdLookup<SkillEffectId>,
+    ) -> Option<&SkillEffectResult> {
+        self.results.get(&skill_effect_name.lookup()?)
+    }
+
+    ///所有效果结果中 key 对应数字的和
+    pub fn sum_number(&self, key: impl IdLookup<SkillPropertyId>) -> f32 {
+        let Some(key) = key.lookup() else {
+            return 0.0;
+        };
+
+        self.results
+            .values()
+            .filter_map(|result| result.get_number(key))
+            .sum()
+    }
+
+    ///任意一个效果结果中 key 对应的值为 true
+    pub fn any_bool(&self, key: impl IdLookup<SkillPropertyId>) -> bool {
+        let Some(key) = key.lookup() else {
+            return false;
+        };
+
+        self.results
+            .values()
+            .any(|result| result.get_bool(key).unwrap_or(false))
+    }
 }
 
 pub trait SkillEffectProcessor: 'static + Sync + Send {
@@ -67,11 +564,12 @@ pub trait SkillEffectProcessor: 'static + Sync + Send {
         target: &SkillComponent,
         skill_effect: &SkillEffect,
         context: &SkillContext,
+        generators: &mut NumberGeneratorContainer,
     ) -> Result<SkillEffectResult, SkillError>;
 }
 
 pub trait ErasedSkillEffectProcessor: 'static + Sync + Send {
-    fn skill_effect_name(&self) -> String;
+    fn skill_effect_name(&self) -> SkillEffectId;
 
     fn update_skill_context(
         &self,
@@ -79,12 +577,13 @@ pub trait ErasedSkillEffectProcessor: 'static + Sync + Send {
         target: &SkillComponent,
         skill_effect: &SkillEffect,
         context: &mut SkillContext,
+        generators: &mut NumberGeneratorContainer,
     ) -> Result<(), SkillError>;
 }
 
 impl<T: SkillEffectProcessor> ErasedSkillEffectProcessor for T {
-    fn skill_effect_name(&self) -> String {
-        T::name()
+    fn skill_effect_name(&self) -> SkillEffectId {
+        SkillEffectId::new(&T::name())
     }
 
     fn update_skill_context(
@@ -93,17 +592,28 @@ impl<T: SkillEffectProcessor> ErasedSkillEffectProcessor for T {
         target: &SkillComponent,
         skill_effct: &SkillEffect,
         context: &mut SkillContext,
+        generators: &mut NumberGeneratorContainer,
     ) -> Result<(), SkillError> {
-        let result = self.process(caster, target, skill_effct, context)?;
-        let skill_effect_name = self.skill_effect_name();
+        let result = self.process(caster, target, skill_effct, context, generators)?;
 
-        context.results.insert(skill_effect_name, result);
+        //同一个技能中的多个同名效果合并结果
+        match context.results.get_mut(&skill_effct.skill_effect_name) {
+            Some(previous) => previous.merge(result),
+            None => {
+                context
+                    .results
+                    .insert(skill_effct.skill_effect_name, result);
+            }
+        }
 
         Ok(())
     }
 }
 
-pub struct SkillEffectProcessorContainer(HashMap<String, Box<dyn ErasedSkillEffectProcessor>>);
+pub struct SkillEffectProcessorContainer {
+    processors: HashMap<SkillEffectId, Box<dyn ErasedSkillEffectProcessor>>,
+    reactions: Vec<SkillEffectId>,
+}
 
 impl Default for SkillEffectProcessorContainer {
     fn default() -> Self {
@@ -117,11 +627,32 @@ impl SkillEffectProcessorContainer {
     }
 
     pub fn empty() -> Self {
-        SkillEffectProcessorContainer(Default::default())
+        SkillEffectProcessorContainer {
+            processors: Default::default(),
+            reactions: vec![],
+        }
     }
 
     pub fn register_skill_effect_processor<T: SkillEffectProcessor>(&mut self, value: T) {
-        self.0.insert(T::name(), Box::new(value));
+        self.processors
+            .insert(SkillEffectId::new(&T::name()), Box::new(value));
+    }
+
+    ///注册目标对技能做出反应的效果，例如反射、荆棘和反击
+    ///
+    ///反应效果按照注册的顺序在技能的所有效果之后执行，参数来自目标的属性，技能中带有同名效果时只执行技能中的效果
+    pub fn register_reaction_processor<T: SkillEffectProcessor>(&mut self, value: T) {
+        let skill_effect_name = SkillEffectId::new(&T::name());
+
+        if !self.reactions.contains(&skill_effect_name) {
+            self.reactions.push(skill_effect_name);
+        }
+
+        self.register_skill_effect_processor(value);
+    }
+
+    pub fn reactions(&self) -> &[SkillEffectId] {
+        &self.reactions
     }
 }
 
@@ -131,15 +662,103 @@ impl SkillEffectProcessorContainer {
         caster: &SkillComponent,
         target: &SkillComponent,
         skill: &Skill,
+        generators: &mut NumberGeneratorContainer,
     ) -> Result<SkillContext, SkillError> {
+        caster.check_tags(skill)?;
+
+        if !skill.target.allows(caster, target) {
+            return Err(SkillError::InvalidTarget {
+                skill_name: skill.skill_name.clone(),
+                target: skill.target,
+            });
+        }
+
         let mut skill_context = SkillContext::default();
 
         for skill_effect in skill.effcts.iter() {
-            if let Some(processor) = self.0.get(&skill_effect.skill_effect_name) {
-                processor.update_skill_context(caster, target, skill_effect, &mut skill_context)?;
+            if let Some(processor) = self.processors.get(&skill_effect.skill_effect_name) {
+                processor.update_skill_context(
+                    caster,
+                    target,
+                    &*skill_effect.at_level(skill.level)?,
+                    &mut skill_context,
+                    generators,
+                )?;
+            }
+        }
+
+        for reaction in self.reactions.iter() {
+            if skill
+                .effcts
+                .iter()
+                .any(|skill_effect| skill_effect.skill_effect_name == *reaction)
+            {
+                continue;
+            }
+
+            if let Some(processor) = self.processors.get(reaction) {
+                processor.update_skill_context(
+                    caster,
+                    target,
+                    &SkillEffect::new(*reaction),
+                    &mut skill_context,
+                    generators,
+                )?;
             }
         }
 
         Ok(skill_context)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        Attribute, AttributeSet, AttributeUtils, DamageSkillEffectProcessor,
+        NumberGeneratorContainer, Skill, SkillComponent, SkillEffect,
+        SkillEffectProcessorContainer, SkillTarget,
+    };
+
+    #[test]
+    fn test_skill_target() {
+        let mut container = SkillEffectProcessorContainer::new();
+        container.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+
+        let caster = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+
+        //没有生命值属性的目标视为存活
+        let mut attribute_set = AttributeSet::default();
+        let mut armor = Attribute::default();
+        armor.attribute_name = "armor".into();
+        armor.set_base_value(5.0);
+        attribute_set.insert_attribute(armor);
+        let target = SkillComponent::new(attribute_set);
+
+        assert!(SkillTarget::Any.allows(&caster, &target));
+        assert!(!SkillTarget::DeadAlly.allows(&caster, &target));
+
+        let skill = Skill::new(
+            "slash",
+            vec![
+                SkillEffect::new("damage_skill_effect")
+                    .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, 10.0),
+            ],
+        );
+        let context = container
+            .execute(
+                &caster,
+                &target,
+                &skill,
+                &mut NumberGeneratorContainer::seeded(1),
+            )
+            .unwrap();
+        assert_eq!(10.0, context.sum_number(DamageSkillEffectProcessor::DAMAGE));
+
+        //生命值为 0 的目标只能被复活
+        let mut dead = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        dead.attribute_set
+            .set_current_value(AttributeUtils::HEALTH, 0.0, &Default::default());
+        assert!(!SkillTarget::Any.allows(&caster, &dead));
+        assert!(SkillTarget::DeadAlly.allows(&caster, &dead));
+    }
+}
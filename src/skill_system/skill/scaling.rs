@@ -0,0 +1,301 @@
+use serde::{Deserialize, Serialize};
+
+///技能效果参数随技能等级变化的方式
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SkillScaling {
+    ///base + per_level * (等级 - 1)
+    Linear { base: f32, per_level: f32 },
+    ///每个等级的值，等级超过表的长度时使用最后一个值
+    Table { values: Vec<f32> },
+    ///四则运算和乘方表达式，可以使用 level 和 base，base 为效果中原本的参数
+    ///
+    ///例如 "base * (1 + 0.2 * (level - 1))"
+    Expression { expression: ScalingExpression },
+}
+
+impl SkillScaling {
+    ///解析表达式，加载数据时解析一次，计算时不再解析
+    pub fn expression(expression: &str) -> Result<Self, String> {
+        Ok(SkillScaling::Expression {
+            expression: ScalingExpression::parse(expression)?,
+        })
+    }
+
+    ///计算 level 级时的参数，base 为效果中原本的参数，不存在时为 0
+    pub fn value(&self, level: u32, base: f32) -> Result<f32, String> {
+        let level = level.max(1);
+
+        match self {
+            SkillScaling::Linear { base, per_level } => Ok(base + per_level * (level - 1) as f32),
+            SkillScaling::Table { values } => values
+                .get(level as usize - 1)
+                .or(values.last())
+                .copied()
+                .ok_or_else(|| "scaling table is empty".to_string()),
+            SkillScaling::Expression { expression } => expression.value(level as f32, base),
+        }
+    }
+}
+
+///解析后的表达式，序列化为原本的字符串
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ScalingExpression {
+    source: String,
+    node: ExpressionNode,
+}
+
+impl ScalingExpression {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut parser = ExpressionParser {
+            chars: source.chars().filter(|c| !c.is_whitespace()).collect(),
+            position: 0,
+        };
+
+        let node = parser.expression()?;
+
+        match parser.peek() {
+            None => Ok(Self {
+                source: source.to_string(),
+                node,
+            }),
+            Some(c) => Err(format!("unexpected '{c}' in expression {source}")),
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    ///结果不是有限的数字时返回错误，例如除以 0
+    pub fn value(&self, level: f32, base: f32) -> Result<f32, String> {
+        let value = self.node.value(level, base);
+
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err(format!(
+                "expression {} is {value} at level {level} with base {base}",
+                self.source
+            ))
+        }
+    }
+}
+
+impl TryFrom<String> for ScalingExpression {
+    type Error = String;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        Self::parse(&source)
+    }
+}
+
+impl From<ScalingExpression> for String {
+    fn from(expression: ScalingExpression) -> Self {
+        expression.source
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ExpressionNode {
+    Number(f32),
+    Level,
+    Base,
+    Negate(Box<ExpressionNode>),
+    Binary(char, Box<ExpressionNode>, Box<ExpressionNode>),
+}
+
+impl ExpressionNode {
+    fn value(&self, level: f32, base: f32) -> f32 {
+        match self {
+            ExpressionNode::Number(value) => *value,
+            ExpressionNode::Level => level,
+            ExpressionNode::Base => base,
+            ExpressionNode::Negate(node) => -node.value(level, base),
+            ExpressionNode::Binary(op, left, right) => {
+                let left = left.value(level, base);
+                let right = right.value(level, base);
+
+                match op {
+                    '+' => left + right,
+                    '-' => left - right,
+                    '*' => left * right,
+                    '/' => left / right,
+                    _ => left.powf(right),
+                }
+            }
+        }
+    }
+
+    fn binary(op: char, left: Self, right: Self) -> Self {
+        ExpressionNode::Binary(op, Box::new(left), Box::new(right))
+    }
+}
+
+///递归下降的表达式解析
+struct ExpressionParser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl ExpressionParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expression(&mut self) -> Result<ExpressionNode, String> {
+        let mut node = self.term()?;
+
+        loop {
+            if self.eat('+') {
+                node = ExpressionNode::binary('+', node, self.term()?);
+            } else if self.eat('-') {
+                node = ExpressionNode::binary('-', node, self.term()?);
+            } else {
+                return Ok(node);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<ExpressionNode, String> {
+        let mut node = self.power()?;
+
+        loop {
+            if self.eat('*') {
+                node = ExpressionNode::binary('*', node, self.power()?);
+            } else if self.eat('/') {
+                node = ExpressionNode::binary('/', node, self.power()?);
+            } else {
+                return Ok(node);
+            }
+        }
+    }
+
+    fn power(&mut self) -> Result<ExpressionNode, String> {
+        let node = self.unary()?;
+
+        if self.eat('^') {
+            Ok(ExpressionNode::binary('^', node, self.power()?))
+        } else {
+            Ok(node)
+        }
+    }
+
+    fn unary(&mut self) -> Result<ExpressionNode, String> {
+        if self.eat('-') {
+            Ok(ExpressionNode::Negate(Box::new(self.unary()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<ExpressionNode, String> {
+        if self.eat('(') {
+            let node = self.expression()?;
+
+            return if self.eat(')') {
+                Ok(node)
+            } else {
+                Err("missing ')' in expression".to_string())
+            };
+        }
+
+        let start = self.position;
+
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+                    self.position += 1;
+                }
+
+                let number = self.chars[start..self.position].iter().collect::<String>();
+                number
+                    .parse()
+                    .map(ExpressionNode::Number)
+                    .map_err(|_| format!("invalid number {number} in expression"))
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    self.position += 1;
+                }
+
+                match self.chars[start..self.position]
+                    .iter()
+                    .collect::<String>()
+                    .as_str()
+                {
+                    "level" => Ok(ExpressionNode::Level),
+                    "base" => Ok(ExpressionNode::Base),
+                    name => Err(format!("unknown variable {name} in expression")),
+                }
+            }
+            Some(c) => Err(format!("unexpected '{c}' in expression")),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::SkillScaling;
+
+    #[test]
+    fn test_skill_scaling() {
+        let linear = SkillScaling::Linear {
+            base: 10.0,
+            per_level: 5.0,
+        };
+        assert_eq!(Ok(10.0), linear.value(1, 0.0));
+        assert_eq!(Ok(20.0), linear.value(3, 0.0));
+
+        let table = SkillScaling::Table {
+            values: vec![1.0, 4.0, 9.0],
+        };
+        assert_eq!(Ok(4.0), table.value(2, 0.0));
+        assert_eq!(Ok(9.0), table.value(5, 0.0));
+        assert!(
+            SkillScaling::Table { values: vec![] }
+                .value(1, 0.0)
+                .is_err()
+        );
+
+        let expression =
+            SkillScaling::expression("base * (1 + 0.5 * (level - 1)) + 2 ^ level / -(-2)").unwrap();
+        //40 * 2 + 8 / 2
+        assert_eq!(Ok(84.0), expression.value(3, 40.0));
+
+        //表达式在反序列化时解析，序列化为原本的字符串
+        let json = r#"{"type":"expression","expression":"base * level"}"#;
+        let scaling: SkillScaling = serde_json::from_str(json).unwrap();
+        assert_eq!(Ok(20.0), scaling.value(2, 10.0));
+        assert_eq!(json, serde_json::to_string(&scaling).unwrap());
+
+        //除以 0 等结果不是有限的数字时返回错误
+        let expression = SkillScaling::expression("base / (level - 1)").unwrap();
+        assert_eq!(Ok(10.0), expression.value(2, 10.0));
+        assert!(expression.value(1, 10.0).is_err());
+        assert!(expression.value(1, 0.0).is_err());
+
+        for invalid in ["level +", "(level", "mana * 2", "level 2"] {
+            assert!(SkillScaling::expression(invalid).is_err(), "{invalid}");
+            let json = format!(r#"{{"type":"expression","expression":"{invalid}"}}"#);
+            assert!(
+                serde_json::from_str::<SkillScaling>(&json).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+}
@@ -0,0 +1,422 @@
+use bevy::platform::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{AttributeConstraintProcessorContainer, AttributeId, AttributeSet, Skill, SkillTag};
+
+///技能的使用限制
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SkillUsage {
+    ///使用之后再次可以使用需要经过的回合数，使用充能时为恢复一次充能需要的回合数，为 0 时充能立即恢复
+    #[serde(default)]
+    pub cooldown: u32,
+    ///最大充能次数，None 表示不使用充能
+    #[serde(default)]
+    pub max_charges: Option<u32>,
+    ///使用时消耗的属性，例如 power
+    #[serde(default, with = "crate::serialize::sorted_map")]
+    pub costs: HashMap<AttributeId, f32>,
+}
+
+impl SkillUsage {
+    pub fn with_cooldown(mut self, cooldown: u32) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    pub fn with_max_charges(mut self, max_charges: u32) -> Self {
+        self.max_charges = Some(max_charges);
+        self
+    }
+
+    pub fn with_cost(mut self, attribute_name: impl Into<AttributeId>, cost: f32) -> Self {
+        self.costs.insert(attribute_name.into(), cost);
+        self
+    }
+}
+
+///单位学会的技能和它的状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LearnedSkill {
+    pub skill: Skill,
+    #[serde(default)]
+    pub usage: SkillUsage,
+    ///剩余的冷却回合数
+    #[serde(default)]
+    pub cooldown: u32,
+    ///剩余的充能次数
+    #[serde(default)]
+    pub charges: u32,
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+impl LearnedSkill {
+    pub fn new(skill: Skill, usage: SkillUsage) -> Self {
+        Self {
+            skill,
+            charges: usage.max_charges.unwrap_or(0),
+            usage,
+            cooldown: 0,
+            disabled: false,
+        }
+    }
+}
+
+///技能无法使用的原因
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum SkillUnavailable {
+    NotLearned,
+    NotEquipped,
+    Disabled,
+    ///被状态禁用，例如沉默禁用带有 magic 标签的技能
+    Blocked {
+        status: String,
+        tag: SkillTag,
+    },
+    ///被眩晕等状态禁止行动
+    Stunned(String),
+    Cooldown(u32),
+    NoCharges,
+    ///属性不足以支付消耗
+    InsufficientCost(AttributeId),
+}
+
+///技能书错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum SkillBookError {
+    AlreadyLearned,
+    NotLearned,
+    AlreadyEquipped,
+    ///装备栏已满
+    SlotsFull,
+}
+
+///单位学会和装备的技能
+///
+///只有装备的技能可以使用，slots 为装备栏的数量，None 表示不限制
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SkillBook {
+    skills: Vec<LearnedSkill>,
+    equipped: Vec<String>,
+    #[serde(default)]
+    pub slots: Option<usize>,
+}
+
+impl SkillBook {
+    pub fn new(slots: Option<usize>) -> Self {
+        Self {
+            slots,
+            ..Default::default()
+        }
+    }
+
+    pub fn learn(&mut self, skill: Skill, usage: SkillUsage) -> Result<(), SkillBookError> {
+        if self.get(&skill.skill_name).is_some() {
+            return Err(SkillBookError::AlreadyLearned);
+        }
+
+        self.skills.push(LearnedSkill::new(skill, usage));
+
+        Ok(())
+    }
+
+    ///学会技能并装备，装备栏已满时技能只会被学会
+    pub fn learn_and_equip(
+        &mut self,
+        skill: Skill,
+        usage: SkillUsage,
+    ) -> Result<(), SkillBookError> {
+        let skill_name = skill.skill_name.clone();
+        self.learn(skill, usage)?;
+        self.equip(&skill_name)
+    }
+
+    ///忘记技能，同时卸下这个技能
+    pub fn forget(&mut self, skill_name: &str) -> Option<LearnedSkill> {
+        let index = self
+            .skills
+            .iter()
+            .position(|learned| learned.skill.skill_name == skill_name)?;
+
+        self.equipped.retain(|name| name != skill_name);
+
+        Some(self.skills.remove(index))
+    }
+
+    pub fn get(&self, skill_name: &str) -> Option<&LearnedSkill> {
+        self.skills
+            .iter()
+            .find(|learned| learned.skill.skill_name == skill_name)
+    }
+
+    pub fn get_mut(&mut self, skill_name: &str) -> Option<&mut LearnedSkill> {
+        self.skills
+            .iter_mut()
+            .find(|learned| learned.skill.skill_name == skill_name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &LearnedSkill> {
+        self.skills.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.skills.is_empty()
+    }
+
+    pub fn equip(&mut self, skill_name: &str) -> Result<(), SkillBookError> {
+        if self.get(skill_name).is_none() {
+            return Err(SkillBookError::NotLearned);
+        }
+
+        if self.is_equipped(skill_name) {
+            return Err(SkillBookError::AlreadyEquipped);
+        }
+
+        if self.slots.is_some_and(|slots| self.equipped.len() >= slots) {
+            return Err(SkillBookError::SlotsFull);
+        }
+
+        self.equipped.push(skill_name.to_string());
+
+        Ok(())
+    }
+
+    pub fn unequip(&mut self, skill_name: &str) -> bool {
+        let len = self.equipped.len();
+        self.equipped.retain(|name| name != skill_name);
+
+        self.equipped.len() != len
+    }
+
+    pub fn is_equipped(&self, skill_name: &str) -> bool {
+        self.equipped.iter().any(|name| name == skill_name)
+    }
+
+    ///按照装备的顺序
+    pub fn equipped(&self) -> impl Iterator<Item = &LearnedSkill> {
+        self.equipped.iter().filter_map(|name| self.get(name))
+    }
+
+    ///升级技能，返回新的等级
+    pub fn upgrade(&mut self, skill_name: &str) -> Option<u32> {
+        self.get_mut(skill_name)
+            .map(|learned| learned.skill.upgrade())
+    }
+
+    ///检查技能现在是否可以使用，attribute_set 用于检查消耗，不包括状态的限制
+    pub fn can_use(
+        &self,
+        skill_name: &str,
+        attribute_set: &AttributeSet,
+    ) -> Result<(), SkillUnavailable> {
+        let learned = self.get(skill_name).ok_or(SkillUnavailable::NotLearned)?;
+
+        if !self.is_equipped(skill_name) {
+            return Err(SkillUnavailable::NotEquipped);
+        }
+
+        if learned.disabled {
+            return Err(SkillUnavailable::Disabled);
+        }
+
+        match learned.usage.max_charges {
+            Some(_) if learned.charges == 0 => return Err(SkillUnavailable::NoCharges),
+            None if learned.cooldown > 0 => {
+                return Err(SkillUnavailable::Cooldown(learned.cooldown));
+            }
+            _ => {}
+        }
+
+        for (name, cost) in learned.usage.costs.iter() {
+            if attribute_set.get_current_value(*name).unwrap_or(0.0) < *cost {
+                return Err(SkillUnavailable::InsufficientCost(*name));
+            }
+        }
+
+        Ok(())
+    }
+
+    ///现在可以使用的技能，按照装备的顺序，不包括状态的限制
+    pub fn usable<'a>(
+        &'a self,
+        attribute_set: &'a AttributeSet,
+    ) -> impl Iterator<Item = &'a LearnedSkill> {
+        self.equipped().filter(|learned| {
+            self.can_use(&learned.skill.skill_name, attribute_set)
+                .is_ok()
+        })
+    }
+
+    ///使用技能，扣除消耗并开始冷却或者消耗一次充能
+    pub fn use_skill(
+        &mut self,
+        skill_name: &str,
+        attribute_set: &mut AttributeSet,
+        container: &AttributeConstraintProcessorContainer,
+    ) -> Result<(), SkillUnavailable> {
+        self.can_use(skill_name, attribute_set)?;
+
+        let Some(learned) = self.get_mut(skill_name) else {
+            return Err(SkillUnavailable::NotLearned);
+        };
+
+        for (name, cost) in learned.usage.costs.iter() {
+            if let Some(value) = attribute_set.get_current_value(*name) {
+                attribute_set.set_current_value(*name, value - cost, container);
+            }
+        }
+
+        match learned.usage.max_charges {
+            //没有恢复时间的充能立即恢复
+            Some(_) if learned.usage.cooldown == 0 => {}
+            Some(_) => {
+                learned.charges -= 1;
+
+                if learned.cooldown == 0 {
+                    learned.cooldown = learned.usage.cooldown;
+                }
+            }
+            None => learned.cooldown = learned.usage.cooldown,
+        }
+
+        Ok(())
+    }
+
+    ///经过一个回合，减少冷却并恢复充能
+    pub fn tick(&mut self) {
+        for learned in self.skills.iter_mut() {
+            if learned.cooldown == 0 {
+                continue;
+            }
+
+            learned.cooldown -= 1;
+
+            if learned.cooldown == 0
+                && let Some(max_charges) = learned.usage.max_charges
+                && learned.charges < max_charges
+            {
+                learned.charges += 1;
+
+                if learned.charges < max_charges {
+                    learned.cooldown = learned.usage.cooldown;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        AttributeConstraintProcessorContainer, AttributeUtils, Skill, SkillBook, SkillBookError,
+        SkillUnavailable, SkillUsage,
+    };
+
+    #[test]
+    fn test_skill_book() {
+        let container = AttributeConstraintProcessorContainer::new();
+        let mut attribute_set = AttributeUtils::new_skill_attribute_set();
+        let mut book = SkillBook::new(Some(2));
+
+        book.learn(
+            Skill::new("fireball", vec![]),
+            SkillUsage::default()
+                .with_cooldown(2)
+                .with_cost(AttributeUtils::POWER, 6.0),
+        )
+        .unwrap();
+        book.learn(
+            Skill::new("dash", vec![]),
+            SkillUsage::default().with_cooldown(2).with_max_charges(2),
+        )
+        .unwrap();
+        book.learn(Skill::new("slash", vec![]), SkillUsage::default())
+            .unwrap();
+
+        assert_eq!(
+            Err(SkillBookError::AlreadyLearned),
+            book.learn(Skill::new("slash", vec![]), SkillUsage::default())
+        );
+        assert_eq!(
+            Err(SkillUnavailable::NotEquipped),
+            book.can_use("fireball", &attribute_set)
+        );
+
+        book.equip("fireball").unwrap();
+        book.equip("dash").unwrap();
+        assert_eq!(Err(SkillBookError::SlotsFull), book.equip("slash"));
+
+        //冷却
+        book.use_skill("fireball", &mut attribute_set, &container)
+            .unwrap();
+        assert_eq!(
+            Some(4.0),
+            attribute_set.get_current_value(AttributeUtils::POWER)
+        );
+        assert_eq!(
+            Err(SkillUnavailable::Cooldown(2)),
+            book.can_use("fireball", &attribute_set)
+        );
+        book.tick();
+        book.tick();
+        assert_eq!(
+            Err(SkillUnavailable::InsufficientCost(
+                AttributeUtils::POWER.into()
+            )),
+            book.can_use("fireball", &attribute_set)
+        );
+
+        //充能
+        book.use_skill("dash", &mut attribute_set, &container)
+            .unwrap();
+        book.use_skill("dash", &mut attribute_set, &container)
+            .unwrap();
+        assert_eq!(
+            Err(SkillUnavailable::NoCharges),
+            book.can_use("dash", &attribute_set)
+        );
+        book.tick();
+        book.tick();
+        assert_eq!(1, book.get("dash").unwrap().charges);
+        book.tick();
+        book.tick();
+        assert_eq!(2, book.get("dash").unwrap().charges);
+        assert_eq!(0, book.get("dash").unwrap().cooldown);
+
+        assert_eq!(
+            vec!["dash"],
+            book.usable(&attribute_set)
+                .map(|learned| learned.skill.skill_name.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        assert!(book.forget("dash").is_some());
+        assert!(!book.is_equipped("dash"));
+        book.equip("slash").unwrap();
+        assert_eq!(Some(2), book.upgrade("slash"));
+    }
+
+    #[test]
+    fn test_charges_without_cooldown() {
+        let container = AttributeConstraintProcessorContainer::new();
+        let mut attribute_set = AttributeUtils::new_skill_attribute_set();
+        let mut book = SkillBook::new(None);
+
+        book.learn_and_equip(
+            Skill::new("blink", vec![]),
+            SkillUsage::default().with_max_charges(2),
+        )
+        .unwrap();
+
+        //冷却为 0 时每次使用之后充能立即恢复
+        for _ in 0..3 {
+            book.use_skill("blink", &mut attribute_set, &container)
+                .unwrap();
+            assert_eq!(2, book.get("blink").unwrap().charges);
+            assert_eq!(0, book.get("blink").unwrap().cooldown);
+        }
+        book.tick();
+        assert!(book.can_use("blink", &attribute_set).is_ok());
+    }
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Skill, SkillTag};
+
+///常用的技能标签
+pub struct SkillTags;
+
+impl SkillTags {
+    pub const PHYSICAL: &'static str = "physical";
+    pub const MAGIC: &'static str = "magic";
+    pub const HEAL: &'static str = "heal";
+    pub const ULTIMATE: &'static str = "ultimate";
+    pub const MELEE: &'static str = "melee";
+}
+
+///单位身上的状态，可以禁用带有某些标签的技能
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkillStatus {
+    pub name: String,
+    ///被禁用的技能标签
+    #[serde(default)]
+    pub blocked_tags: Vec<SkillTag>,
+    ///剩余的回合数，None 表示不会自动结束
+    #[serde(default)]
+    pub remaining_turns: Option<u32>,
+    ///眩晕，单位无法使用任何技能，添加时打断可以被打断的蓄力或者引导
+    #[serde(default)]
+    pub stun: bool,
+    ///施加状态的单位，例如嘲讽的施法者
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<u32>,
+}
+
+impl SkillStatus {
+    pub const SILENCE: &'static str = "silence";
+    pub const DISARM: &'static str = "disarm";
+    pub const STUN: &'static str = "stun";
+    pub const TAUNT: &'static str = "taunt";
+
+    pub fn new(name: &str, remaining_turns: Option<u32>) -> Self {
+        Self {
+            name: name.to_string(),
+            blocked_tags: vec![],
+            remaining_turns,
+            stun: false,
+            source: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: u32) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn with_blocked_tag(mut self, tag: impl Into<SkillTag>) -> Self {
+        self.blocked_tags.push(tag.into());
+        self
+    }
+
+    ///沉默，禁用法术
+    pub fn silence(remaining_turns: Option<u32>) -> Self {
+        Self::new(Self::SILENCE, remaining_turns).with_blocked_tag(SkillTags::MAGIC)
+    }
+
+    ///缴械，禁用物理技能
+    pub fn disarm(remaining_turns: Option<u32>) -> Self {
+        Self::new(Self::DISARM, remaining_turns).with_blocked_tag(SkillTags::PHYSICAL)
+    }
+
+    ///眩晕，禁用所有技能
+    pub fn stun(remaining_turns: Option<u32>) -> Self {
+        Self {
+            stun: true,
+            ..Self::new(Self::STUN, remaining_turns)
+        }
+    }
+
+    ///嘲讽，单位只能攻击 source
+    pub fn taunt(source: u32, remaining_turns: Option<u32>) -> Self {
+        Self::new(Self::TAUNT, remaining_turns).with_source(source)
+    }
+
+    ///技能被禁用时返回禁用它的标签
+    pub fn blocked_tag(&self, skill: &Skill) -> Option<SkillTag> {
+        self.blocked_tags
+            .iter()
+            .find(|tag| skill.tags.contains(tag))
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        AttributeUtils, DamageSkillEffectProcessor, NumberGeneratorContainer, Skill,
+        SkillComponent, SkillEffect, SkillEffectProcessorContainer, SkillError, SkillStatus,
+        SkillTags, SkillUnavailable, SkillUsage,
+    };
+
+    fn new_skill(skill_name: &str, tag: &str) -> Skill {
+        Skill::new(
+            skill_name,
+            vec![
+                SkillEffect::new("damage_skill_effect")
+                    .with_value(DamageSkillEffectProcessor::MIN_DAMAGE, 10.0),
+            ],
+        )
+        .with_tag(tag)
+    }
+
+    #[test]
+    fn test_skill_status() {
+        let mut container = SkillEffectProcessorContainer::new();
+        container.register_skill_effect_processor(DamageSkillEffectProcessor::default());
+
+        let mut caster = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        let target = SkillComponent::new(AttributeUtils::new_skill_attribute_set());
+        let fireball = new_skill("fireball", SkillTags::MAGIC);
+        let slash = new_skill("slash", SkillTags::PHYSICAL);
+
+        assert!(fireball.has_tag(SkillTags::MAGIC));
+        assert!(!fireball.has_tag(SkillTags::PHYSICAL));
+
+        caster.add_status(SkillStatus::silence(Some(2)));
+        assert!(matches!(
+            container.execute(
+                &caster,
+                &target,
+                &fireball,
+                &mut NumberGeneratorContainer::seeded(1)
+            ),
+            Err(SkillError::Blocked { tag, .. }) if tag == SkillTags::MAGIC
+        ));
+        assert!(matches!(
+            caster.can_use(&fireball),
+            Err(SkillUnavailable::Blocked { status, .. }) if status == SkillStatus::SILENCE
+        ));
+        assert!(caster.can_use(&slash).is_ok());
+
+        //技能书中的技能同样由状态禁用
+        for skill in [fireball.clone(), slash.clone()] {
+            caster
+                .skill_book
+                .learn_and_equip(skill, SkillUsage::default())
+                .unwrap();
+        }
+        assert_eq!(
+            vec!["slash"],
+            caster
+                .usable_skills()
+                .map(|skill| skill.skill_name.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        //重复添加时刷新持续时间
+        caster.add_status(SkillStatus::silence(Some(1)));
+        caster.add_status(SkillStatus::disarm(None));
+        assert_eq!(2, caster.statuses.len());
+        assert!(caster.can_use(&slash).is_err());
+
+        let expired = caster.tick_statuses();
+        assert_eq!(SkillStatus::SILENCE, expired[0].name);
+        assert!(caster.can_use(&fireball).is_ok());
+        assert!(caster.has_status(SkillStatus::DISARM));
+        assert!(caster.remove_status(SkillStatus::DISARM).is_some());
+        assert!(caster.can_use(&slash).is_ok());
+    }
+}
@@ -0,0 +1,53 @@
+use crate::NumberGeneratorContainer;
+
+use super::{
+    SkillComponent, SkillContext, SkillEffect, SkillEffectProcessor, SkillEffectResult, SkillError,
+};
+
+///召唤效果
+///
+///使用名为 template 的模板召唤一个与施法者同队的单位，lifetime 大于 0 时召唤物在 lifetime 个回合后消失
+///
+///召唤物由 Battle 创建为战斗中的单位，不是 bevy 的实体
+pub struct SummonSkillEffectProcessor;
+
+impl SummonSkillEffectProcessor {
+    pub const TEMPLATE: &'static str = "template";
+    pub const LIFETIME: &'static str = "lifetime";
+
+    ///召唤物的模板名称，由战斗创建召唤物
+    pub const SUMMON: &'static str = "summon";
+    ///召唤物存在的回合数，为 0 时一直存在到召唤者死亡
+    pub const SUMMON_LIFETIME: &'static str = "summon_lifetime";
+}
+
+impl SkillEffectProcessor for SummonSkillEffectProcessor {
+    fn name() -> String {
+        "summon_skill_effect".to_string()
+    }
+
+    fn process(
+        &self,
+        _caster: &SkillComponent,
+        _target: &SkillComponent,
+        skill_effect: &SkillEffect,
+        _context: &SkillContext,
+        _generators: &mut NumberGeneratorContainer,
+    ) -> Result<SkillEffectResult, SkillError> {
+        let mut res = SkillEffectResult::default();
+
+        if let Some(template) = skill_effect.get_text(Self::TEMPLATE) {
+            res.set_value(Self::SUMMON, template);
+            res.set_value(
+                Self::SUMMON_LIFETIME,
+                skill_effect
+                    .get_number(Self::LIFETIME)
+                    .unwrap_or(0.0)
+                    .floor()
+                    .max(0.0),
+            );
+        }
+
+        Ok(res)
+    }
+}
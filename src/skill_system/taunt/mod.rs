@@ -0,0 +1,53 @@
+use crate::NumberGeneratorContainer;
+
+use super::{
+    SkillComponent, SkillContext, SkillEffect, SkillEffectProcessor, SkillEffectResult, SkillError,
+};
+
+///嘲讽效果
+///
+///目标对施法者增加 threat 点仇恨，turns 大于 0 时目标在 turns 个回合中只能攻击施法者
+pub struct TauntSkillEffectProcessor;
+
+impl TauntSkillEffectProcessor {
+    pub const THREAT: &'static str = "threat";
+    pub const TURNS: &'static str = "turns";
+
+    ///嘲讽的回合数，由战斗为目标添加嘲讽状态
+    pub const TAUNT_TURNS: &'static str = "taunt_turns";
+}
+
+impl SkillEffectProcessor for TauntSkillEffectProcessor {
+    fn name() -> String {
+        "taunt_skill_effect".to_string()
+    }
+
+    fn process(
+        &self,
+        _caster: &SkillComponent,
+        _target: &SkillComponent,
+        skill_effect: &SkillEffect,
+        _context: &SkillContext,
+        _generators: &mut NumberGeneratorContainer,
+    ) -> Result<SkillEffectResult, SkillError> {
+        let mut res = SkillEffectResult::default();
+
+        res.set_value(
+            Self::THREAT,
+            skill_effect
+                .get_number(Self::THREAT)
+                .unwrap_or(0.0)
+                .max(0.0),
+        );
+        res.set_value(
+            Self::TAUNT_TURNS,
+            skill_effect
+                .get_number(Self::TURNS)
+                .unwrap_or(0.0)
+                .floor()
+                .max(0.0),
+        );
+
+        Ok(res)
+    }
+}
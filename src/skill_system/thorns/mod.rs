@@ -0,0 +1,56 @@
+use crate::{
+    AttributeUtils, DamagePipeline, DamageSkillEffectProcessor, NumberGeneratorContainer,
+    ReflectSkillEffectProcessor,
+};
+
+use super::{
+    SkillComponent, SkillContext, SkillEffect, SkillEffectProcessor, SkillEffectResult, SkillError,
+};
+
+///荆棘效果，作为反应效果注册时在每个技能之后执行
+///
+///之前的效果命中时，施法者受到 thorns_damage + 目标的荆棘属性的伤害，与造成的伤害无关，之后经过 pipeline 的所有阶段
+#[derive(Default)]
+pub struct ThornsSkillEffectProcessor {
+    pub pipeline: DamagePipeline,
+}
+
+impl ThornsSkillEffectProcessor {
+    pub const THORNS_DAMAGE: &'static str = "thorns_damage";
+
+    pub const CASTER_DAMAGE: &'static str = ReflectSkillEffectProcessor::CASTER_DAMAGE;
+}
+
+impl SkillEffectProcessor for ThornsSkillEffectProcessor {
+    fn name() -> String {
+        "thorns_skill_effect".to_string()
+    }
+
+    fn process(
+        &self,
+        caster: &SkillComponent,
+        target: &SkillComponent,
+        skill_effect: &SkillEffect,
+        context: &SkillContext,
+        _generators: &mut NumberGeneratorContainer,
+    ) -> Result<SkillEffectResult, SkillError> {
+        let damage = if context.any_bool(DamageSkillEffectProcessor::HIT) {
+            skill_effect.get_number(Self::THORNS_DAMAGE).unwrap_or(0.0)
+                + target
+                    .attribute_set
+                    .get_current_value(AttributeUtils::THORNS)
+                    .unwrap_or(0.0)
+        } else {
+            0.0
+        };
+
+        Ok(ReflectSkillEffectProcessor::retaliate(
+            &self.pipeline,
+            damage,
+            caster,
+            target,
+            skill_effect,
+            context,
+        ))
+    }
+}